@@ -0,0 +1,52 @@
+use anchor_lang::{prelude::*, system_program};
+
+/// Grow an account created with an older layout, inserting zeroed bytes for the new fields.
+/// The payer tops up the rent of the new length, accounts without the old length are left untouched
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_account_layout<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    discriminator: [u8; 8],
+    old_len: usize,
+    new_len: usize,
+    insert_offset: usize,
+    insert_len: usize,
+) -> Result<()> {
+    // check that the account has the expected type
+    {
+        let data = account.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != discriminator {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+    }
+
+    if account.data_len() != old_len {
+        msg!("account already migrated");
+        return Ok(());
+    }
+
+    msg!("realloc account from {} to {}", old_len, new_len);
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(new_len);
+    let missing_lamports = rent_exempt_lamports.saturating_sub(account.lamports());
+    if missing_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            missing_lamports,
+        )?;
+    }
+    account.realloc(new_len, true)?;
+
+    // shift the fields following the inserted ones
+    let mut data = account.try_borrow_mut_data()?;
+    data.copy_within(insert_offset..old_len, insert_offset + insert_len);
+    data[insert_offset..insert_offset + insert_len].fill(0);
+
+    Ok(())
+}
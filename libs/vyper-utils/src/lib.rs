@@ -1,3 +1,4 @@
+pub mod account_migration;
 pub mod constants;
 pub mod rate_common;
 pub mod redeem_logic_common;
@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
 
+/// Offset of refreshed_unix_timestamp in the rate plugin states, after the discriminator, the fair value and the refreshed slot
+pub const REFRESHED_UNIX_TIMESTAMP_OFFSET: usize = 8 + 16 * 10 + 8;
+
+/// Length of refreshed_unix_timestamp, missing in the rate plugin states created before it was added
+pub const REFRESHED_UNIX_TIMESTAMP_LEN: usize = 8;
+
 #[error_code]
 pub enum RateErrors {
     #[msg("generic error")]
@@ -2,10 +2,12 @@ pub mod errors;
 
 use crate::errors::RateMockErrorCode;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use vyper_utils::account_migration::migrate_account_layout;
+use vyper_utils::rate_common::{REFRESHED_UNIX_TIMESTAMP_LEN, REFRESHED_UNIX_TIMESTAMP_OFFSET};
 
 #[cfg(not(feature = "no-entrypoint"))]
 solana_security_txt::security_txt! {
@@ -31,10 +33,15 @@ pub mod rate_mock {
         let rate_data = &mut ctx.accounts.rate_data;
         rate_data.fair_value = [dec!(1).serialize(); 10];
         rate_data.refreshed_slot = clock.slot;
+        rate_data.refreshed_unix_timestamp = clock.unix_timestamp;
         rate_data.authority = ctx.accounts.authority.key();
 
         msg!("rate_data.fair_value: {:?}", rate_data.fair_value);
         msg!("rate_data.refreshed_slot: {}", rate_data.refreshed_slot);
+        msg!(
+            "rate_data.refreshed_unix_timestamp: {}",
+            rate_data.refreshed_unix_timestamp
+        );
         msg!("rate_data.authority: {}", rate_data.authority);
 
         Ok(())
@@ -49,9 +56,14 @@ pub mod rate_mock {
             .ok_or(RateMockErrorCode::MathError)?
             .serialize();
         rate_data.refreshed_slot = clock.slot;
+        rate_data.refreshed_unix_timestamp = clock.unix_timestamp;
 
         msg!("rate_data.fair_value: {:?}", rate_data.fair_value);
         msg!("rate_data.refreshed_slot: {}", rate_data.refreshed_slot);
+        msg!(
+            "rate_data.refreshed_unix_timestamp: {}",
+            rate_data.refreshed_unix_timestamp
+        );
 
         Ok(())
     }
@@ -62,11 +74,32 @@ pub mod rate_mock {
         let clock = Clock::get()?;
         let rate_data = &mut ctx.accounts.rate_data;
         rate_data.refreshed_slot = clock.slot;
+        rate_data.refreshed_unix_timestamp = clock.unix_timestamp;
 
         msg!("rate_data.refreshed_slot: {}", rate_data.refreshed_slot);
+        msg!(
+            "rate_data.refreshed_unix_timestamp: {}",
+            rate_data.refreshed_unix_timestamp
+        );
 
         Ok(())
     }
+
+    pub fn migrate(ctx: Context<MigrateContext>) -> Result<()> {
+        msg!("rate-mock: migrate");
+
+        // insert the refreshed unix timestamp, it is zero until the next refresh
+        migrate_account_layout(
+            &ctx.accounts.rate_data,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateState::discriminator(),
+            RateState::LEN - REFRESHED_UNIX_TIMESTAMP_LEN,
+            RateState::LEN,
+            REFRESHED_UNIX_TIMESTAMP_OFFSET,
+            REFRESHED_UNIX_TIMESTAMP_LEN,
+        )
+    }
 }
 
 #[derive(Accounts)]
@@ -107,10 +140,24 @@ pub struct RefreshRateContext<'info> {
     pub rate_data: Account<'info, RateState>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateContext<'info> {
+    /// Signer account, pays for the additional rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: deserialized after the migration, the stored layout can be older than the current one
+    #[account(mut, owner = crate::ID)]
+    pub rate_data: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct RateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
     pub authority: Pubkey,
 }
 
@@ -118,6 +165,7 @@ impl RateState {
     pub const LEN: usize = 8 + // discriminator
     16*10 + // pub fair_value: [[u8; 16]; 10],
     8 + // pub refreshed_slot: u64,
+    8 + // pub refreshed_unix_timestamp: i64,
     32 // pub authority: Pubkey,
     ;
 }
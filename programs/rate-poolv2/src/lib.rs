@@ -4,9 +4,11 @@ mod state;
 use crate::errors::RatePoolv2ErrorCode;
 use crate::state::SupplyWrapper;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 use anchor_spl::token::{Mint, TokenAccount};
 use rust_decimal::Decimal;
+use vyper_utils::account_migration::migrate_account_layout;
+use vyper_utils::rate_common::{REFRESHED_UNIX_TIMESTAMP_LEN, REFRESHED_UNIX_TIMESTAMP_OFFSET};
 
 #[cfg(not(feature = "no-entrypoint"))]
 solana_security_txt::security_txt! {
@@ -55,9 +57,11 @@ pub mod rate_poolv2 {
             rate_data.fair_value[i] = dec.serialize();
         }
 
-        // set refreshed slot
+        // set refreshed slot and timestamp
 
-        rate_data.refreshed_slot = Clock::get()?.slot;
+        let clock = Clock::get()?;
+        rate_data.refreshed_slot = clock.slot;
+        rate_data.refreshed_unix_timestamp = clock.unix_timestamp;
 
         Ok(())
     }
@@ -86,12 +90,30 @@ pub mod rate_poolv2 {
             rate_data.fair_value[i] = dec.serialize();
         }
 
-        // set refreshed slot
+        // set refreshed slot and timestamp
 
-        rate_data.refreshed_slot = Clock::get()?.slot;
+        let clock = Clock::get()?;
+        rate_data.refreshed_slot = clock.slot;
+        rate_data.refreshed_unix_timestamp = clock.unix_timestamp;
 
         Ok(())
     }
+
+    pub fn migrate(ctx: Context<MigrateContext>) -> Result<()> {
+        msg!("rate-poolv2: migrate");
+
+        // insert the refreshed unix timestamp, it is zero until the next refresh
+        migrate_account_layout(
+            &ctx.accounts.rate_data,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateState::discriminator(),
+            RateState::LEN - REFRESHED_UNIX_TIMESTAMP_LEN,
+            RateState::LEN,
+            REFRESHED_UNIX_TIMESTAMP_OFFSET,
+            REFRESHED_UNIX_TIMESTAMP_LEN,
+        )
+    }
 }
 
 #[derive(Accounts)]
@@ -161,10 +183,24 @@ pub struct RefreshRateContext<'info> {
     pub quote_token_account: Account<'info, TokenAccount>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateContext<'info> {
+    /// Signer account, pays for the additional rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: deserialized after the migration, the stored layout can be older than the current one
+    #[account(mut, owner = crate::ID)]
+    pub rate_data: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct RateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
 
     /// Mint of the lp tokens
     pub lp_mint: Pubkey,
@@ -186,6 +222,7 @@ impl RateState {
     pub const LEN: usize = 8 + // discriminator
     16*10 +     // pub fair_value: [[u8; 16]; 10],
     8 +         // pub refreshed_slot: u64,
+    8 +         // pub refreshed_unix_timestamp: i64,
     32 +        // pub lp_mint: Pubkey,
     32 +        // pub base_mint: Pubkey,
     32 +        // pub quote_mint: Pubkey,
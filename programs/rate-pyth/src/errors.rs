@@ -12,4 +12,7 @@ pub enum RatePythErrorCode {
 
     #[msg("math error")]
     MathError,
+
+    #[msg("missing oracle publish time")]
+    MissingOraclePublishTime,
 }
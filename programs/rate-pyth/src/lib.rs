@@ -2,10 +2,12 @@ pub mod errors;
 
 use crate::errors::RatePythErrorCode;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 use pyth_sdk_solana::{load_price_feed_from_account_info, Price, PriceFeed};
 use rust_decimal::{Decimal, MathematicalOps};
 use rust_decimal_macros::dec;
+use vyper_utils::account_migration::migrate_account_layout;
+use vyper_utils::rate_common::{REFRESHED_UNIX_TIMESTAMP_LEN, REFRESHED_UNIX_TIMESTAMP_OFFSET};
 
 #[cfg(not(feature = "no-entrypoint"))]
 solana_security_txt::security_txt! {
@@ -69,6 +71,22 @@ pub mod rate_pyth {
 
         Ok(())
     }
+
+    pub fn migrate(ctx: Context<MigrateContext>) -> Result<()> {
+        msg!("rate-pyth: migrate");
+
+        // insert the refreshed unix timestamp, it is zero until the next refresh
+        migrate_account_layout(
+            &ctx.accounts.rate_data,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateState::discriminator(),
+            RateState::LEN - REFRESHED_UNIX_TIMESTAMP_LEN,
+            RateState::LEN,
+            REFRESHED_UNIX_TIMESTAMP_OFFSET,
+            REFRESHED_UNIX_TIMESTAMP_LEN,
+        )
+    }
 }
 
 #[derive(Accounts)]
@@ -93,10 +111,24 @@ pub struct RefreshRateContext<'info> {
     pub rate_data: Account<'info, RateState>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateContext<'info> {
+    /// Signer account, pays for the additional rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: deserialized after the migration, the stored layout can be older than the current one
+    #[account(mut, owner = crate::ID)]
+    pub rate_data: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct RateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
     pub pyth_oracles: [Option<Pubkey>; 10],
 }
 
@@ -104,6 +136,7 @@ impl RateState {
     pub const LEN: usize = 8 + // discriminator
     16*10 +     // pub fair_value: [[u8; 16]; 10],
     8 +         // pub refreshed_slot: u64,
+    8 +         // pub refreshed_unix_timestamp: i64,
     10*(1+32)   // pub pyth_oracles: [Option<Pubkey>; 10],
     ;
 }
@@ -112,6 +145,7 @@ fn set_data_from_oracles(
     rate_data: &mut Account<RateState>,
     oracles: &[AccountInfo],
 ) -> Result<()> {
+    let mut oldest_publish_time: Option<i64> = None;
     for (i, oracle) in oracles.iter().enumerate() {
         if let Some(serialized_oracle) = rate_data.pyth_oracles[i] {
             // check that the provided aggregator is the correct one
@@ -121,6 +155,11 @@ fn set_data_from_oracles(
             let price_feed: PriceFeed = load_price_feed_from_account_info(oracle).unwrap();
             let current_price: Price = price_feed.get_current_price().unwrap();
 
+            if oldest_publish_time.is_none() || oldest_publish_time > Some(price_feed.publish_time)
+            {
+                oldest_publish_time = Some(price_feed.publish_time);
+            }
+
             #[cfg(feature = "debug")]
             {
                 msg!("+ current pyth feed: {:?}", price_feed);
@@ -139,11 +178,17 @@ fn set_data_from_oracles(
         }
     }
 
-    // save current slot
+    // save current slot and the oldest publish time of the oracles
     rate_data.refreshed_slot = Clock::get()?.slot;
+    rate_data.refreshed_unix_timestamp =
+        oldest_publish_time.ok_or(RatePythErrorCode::MissingOraclePublishTime)?;
 
     msg!("rate_data.fair_value {:?}", rate_data.fair_value);
     msg!("rate_data.refreshed_slot: {}", rate_data.refreshed_slot);
+    msg!(
+        "rate_data.refreshed_unix_timestamp: {}",
+        rate_data.refreshed_unix_timestamp
+    );
 
     Ok(())
 }
@@ -13,4 +13,7 @@ pub enum RateSwitchboardErrorCode {
 
     #[msg("math error")]
     MathError,
+
+    #[msg("missing aggregator timestamp")]
+    MissingAggregatorTimestamp,
 }
@@ -2,9 +2,11 @@ pub mod errors;
 
 use crate::errors::RateSwitchboardErrorCode;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 use switchboard_v2::{AggregatorAccountData, SWITCHBOARD_V2_DEVNET, SWITCHBOARD_V2_MAINNET};
+use vyper_utils::account_migration::migrate_account_layout;
+use vyper_utils::rate_common::{REFRESHED_UNIX_TIMESTAMP_LEN, REFRESHED_UNIX_TIMESTAMP_OFFSET};
 
 #[cfg(not(feature = "no-entrypoint"))]
 solana_security_txt::security_txt! {
@@ -74,6 +76,22 @@ pub mod rate_switchboard {
 
         Ok(())
     }
+
+    pub fn migrate(ctx: Context<MigrateContext>) -> Result<()> {
+        msg!("rate-switchboard: migrate");
+
+        // insert the refreshed unix timestamp, it is zero until the next refresh
+        migrate_account_layout(
+            &ctx.accounts.rate_data,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateState::discriminator(),
+            RateState::LEN - REFRESHED_UNIX_TIMESTAMP_LEN,
+            RateState::LEN,
+            REFRESHED_UNIX_TIMESTAMP_OFFSET,
+            REFRESHED_UNIX_TIMESTAMP_LEN,
+        )
+    }
 }
 
 #[derive(Accounts)]
@@ -98,10 +116,24 @@ pub struct RefreshRateContext<'info> {
     pub rate_data: Account<'info, RateState>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateContext<'info> {
+    /// Signer account, pays for the additional rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: deserialized after the migration, the stored layout can be older than the current one
+    #[account(mut, owner = crate::ID)]
+    pub rate_data: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct RateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
     pub switchboard_aggregators: [Option<Pubkey>; 10],
 }
 
@@ -109,6 +141,7 @@ impl RateState {
     pub const LEN: usize = 8 + // discriminator
     16*10 +     // pub fair_value: [[u8; 16]; 10],
     8 +         // pub refreshed_slot: u64,
+    8 +         // pub refreshed_unix_timestamp: i64,
     10*(1+32)   // pub switchboard_aggregators: [Option<Pubkey>; 10],
     ;
 }
@@ -119,6 +152,7 @@ fn set_data_from_aggregators(
 ) -> Result<()> {
     // read the aggregators data
     let mut oldest_slot: Option<u64> = None;
+    let mut oldest_unix_timestamp: Option<i64> = None;
     for (i, aggr) in aggregators.iter().enumerate() {
         if let Some(serialized_aggregator) = rate_data.switchboard_aggregators[i] {
             // check that the provided aggregator is the correct one
@@ -133,6 +167,12 @@ fn set_data_from_aggregators(
                 oldest_slot = Some(latest_confirmed_round.round_open_slot);
             }
 
+            if oldest_unix_timestamp.is_none()
+                || oldest_unix_timestamp > Some(latest_confirmed_round.round_open_timestamp)
+            {
+                oldest_unix_timestamp = Some(latest_confirmed_round.round_open_timestamp);
+            }
+
             match std::str::from_utf8(&feed.name) {
                 Ok(feed_name) => msg!("switchboard aggregator {}", feed_name),
                 _ => msg!("switchboard aggregator"),
@@ -150,9 +190,15 @@ fn set_data_from_aggregators(
     }
 
     rate_data.refreshed_slot = oldest_slot.ok_or(RateSwitchboardErrorCode::GenericError)?;
+    rate_data.refreshed_unix_timestamp =
+        oldest_unix_timestamp.ok_or(RateSwitchboardErrorCode::MissingAggregatorTimestamp)?;
 
     msg!("rate_data.fair_value {:?}", rate_data.fair_value);
     msg!("rate_data.refreshed_slot: {}", rate_data.refreshed_slot);
+    msg!(
+        "rate_data.refreshed_unix_timestamp: {}",
+        rate_data.refreshed_unix_timestamp
+    );
 
     Ok(())
 }
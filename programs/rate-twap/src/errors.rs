@@ -13,4 +13,7 @@ pub enum RateTwapErrorCode {
 
     #[msg("another too recent sample")]
     AnotherTooRecentSample,
+
+    #[msg("unknown rate state layout")]
+    UnknownLayout,
 }
@@ -43,6 +43,7 @@ pub fn handler(ctx: Context<InitializeContext>, input_data: InitializeInput) ->
     rate_state.sampling_data.try_add(
         rate_state_source.fair_value.map(Decimal::deserialize),
         rate_state_source.refreshed_slot,
+        rate_state_source.refreshed_unix_timestamp,
     )?;
 
     rate_state.compute_twap()?;
@@ -57,4 +58,5 @@ pub fn handler(ctx: Context<InitializeContext>, input_data: InitializeInput) ->
 pub struct CommonRateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
 }
@@ -0,0 +1,241 @@
+use crate::{errors::RateTwapErrorCode, state::RateState};
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateContext<'info> {
+    /// payer account, pays for the additional rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: deserialized after the migration, the stored layout can be older than the current one
+    #[account(mut, owner = crate::ID)]
+    pub rate_state: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Offset of the refreshed slot, after the discriminator and the fair value
+const REFRESHED_SLOT_OFFSET: usize = 8 + 10 * 16;
+
+/// Offset of the max samples size in the layout before the unix timestamps were added,
+/// after the refreshed slot, the rate state source and the min slot delta
+const V1_MAX_SAMPLES_SIZE_OFFSET: usize = REFRESHED_SLOT_OFFSET + 8 + 32 + 8;
+
+/// Offset of the max samples size in the current layout
+const MAX_SAMPLES_SIZE_OFFSET: usize = V1_MAX_SAMPLES_SIZE_OFFSET + 8;
+
+/// SampleRecord length before the unix timestamp was added: value and slot
+const V1_SAMPLE_RECORD_LEN: usize = 10 * 16 + 8;
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// Check if the data matches the layout before the unix timestamps were added:
+/// the account length matches the max samples size, the samples fit in it
+/// and the refreshed slot is the one of the most recent sample
+fn is_v1_layout(data: &[u8]) -> bool {
+    let max_samples_size = match read_u32(data, V1_MAX_SAMPLES_SIZE_OFFSET) {
+        Some(v) => v as usize,
+        None => return false,
+    };
+    if data.len() != RateState::len(max_samples_size) - 8 - 8 * max_samples_size {
+        return false;
+    }
+
+    let samples_offset = V1_MAX_SAMPLES_SIZE_OFFSET + 4 + 4;
+    let samples_len = match read_u32(data, V1_MAX_SAMPLES_SIZE_OFFSET + 4) {
+        Some(v) if v > 0 && v as usize <= max_samples_size => v as usize,
+        _ => return false,
+    };
+    let most_recent_slot = (0..samples_len)
+        .filter_map(|i| read_u64(data, samples_offset + i * V1_SAMPLE_RECORD_LEN + 10 * 16))
+        .max();
+
+    most_recent_slot.is_some() && most_recent_slot == read_u64(data, REFRESHED_SLOT_OFFSET)
+}
+
+/// Check if the account length matches the current layout
+fn is_current_layout(data: &[u8]) -> bool {
+    match read_u32(data, MAX_SAMPLES_SIZE_OFFSET) {
+        Some(max_samples_size) => data.len() == RateState::len(max_samples_size as usize),
+        None => false,
+    }
+}
+
+/// Rebuild a rate state created before the unix timestamps were added,
+/// the refreshed unix timestamp and the samples unix timestamps are zero.
+/// Returns None if the account already has the current layout
+fn migrate_v1_data(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let is_v1 = is_v1_layout(data);
+    let is_current = is_current_layout(data);
+
+    match (is_v1, is_current) {
+        (false, true) => return Ok(None),
+        (true, false) => {}
+        _ => return err!(RateTwapErrorCode::UnknownLayout),
+    }
+
+    let max_samples_size = read_u32(data, V1_MAX_SAMPLES_SIZE_OFFSET).unwrap() as usize;
+    let samples_offset = V1_MAX_SAMPLES_SIZE_OFFSET + 4 + 4;
+
+    let mut migrated = Vec::with_capacity(RateState::len(max_samples_size));
+    // discriminator, fair value and refreshed slot
+    migrated.extend_from_slice(&data[..REFRESHED_SLOT_OFFSET + 8]);
+    // refreshed_unix_timestamp
+    migrated.extend_from_slice(&0i64.to_le_bytes());
+    // rate state source, min slot delta, max samples size and samples vec length
+    migrated.extend_from_slice(&data[REFRESHED_SLOT_OFFSET + 8..samples_offset]);
+    // samples and unused capacity, each record followed by its unix timestamp
+    for record in data[samples_offset..].chunks_exact(V1_SAMPLE_RECORD_LEN) {
+        migrated.extend_from_slice(record);
+        migrated.extend_from_slice(&0i64.to_le_bytes());
+    }
+
+    Ok(Some(migrated))
+}
+
+pub fn handler(ctx: Context<MigrateContext>) -> Result<()> {
+    let rate_state_info = ctx.accounts.rate_state.to_account_info();
+
+    let migrated = {
+        let data = rate_state_info.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != RateState::discriminator() {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        migrate_v1_data(&data)?
+    };
+
+    let migrated = match migrated {
+        Some(v) => v,
+        None => {
+            msg!("rate_state already migrated");
+            return Ok(());
+        }
+    };
+
+    msg!(
+        "realloc rate_state from {} to {}",
+        rate_state_info.data_len(),
+        migrated.len()
+    );
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(migrated.len());
+    let missing_lamports = rent_exempt_lamports.saturating_sub(rate_state_info.lamports());
+    if missing_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: rate_state_info.clone(),
+                },
+            ),
+            missing_lamports,
+        )?;
+    }
+    rate_state_info.realloc(migrated.len(), false)?;
+    rate_state_info
+        .try_borrow_mut_data()?
+        .copy_from_slice(&migrated);
+
+    // check that the migrated account can be deserialized
+    let _rate_state = Account::<RateState>::try_from(&rate_state_info)?;
+
+    #[cfg(feature = "debug")]
+    msg!("sampling_data: {:?}", _rate_state.sampling_data);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::state::SamplingData;
+
+    /// Serialize a rate state with the layout before the unix timestamps were added
+    fn v1_data(max_samples_size: u32, samples: &[(Decimal, u64)]) -> Vec<u8> {
+        let mut data = RateState::discriminator().to_vec();
+        let most_recent = samples.iter().max_by_key(|(_, slot)| *slot).unwrap();
+        for _ in 0..10 {
+            data.extend_from_slice(&most_recent.0.serialize());
+        }
+        data.extend_from_slice(&most_recent.1.to_le_bytes());
+        data.extend_from_slice(&[7; 32]);
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&max_samples_size.to_le_bytes());
+        data.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        for (value, slot) in samples {
+            for _ in 0..10 {
+                data.extend_from_slice(&value.serialize());
+            }
+            data.extend_from_slice(&slot.to_le_bytes());
+        }
+        data.resize(
+            data.len() + (max_samples_size as usize - samples.len()) * V1_SAMPLE_RECORD_LEN,
+            0,
+        );
+        data
+    }
+
+    #[test]
+    fn test_migrate_v1_data() {
+        let data = v1_data(4, &[(dec!(1), 10), (dec!(3), 20)]);
+        let migrated = migrate_v1_data(&data).unwrap().unwrap();
+        assert_eq!(migrated.len(), RateState::len(4));
+
+        let rate_state = RateState::try_deserialize(&mut migrated.as_slice()).unwrap();
+        assert_eq!(rate_state.refreshed_slot, 20);
+        assert_eq!(rate_state.refreshed_unix_timestamp, 0);
+        assert_eq!(
+            rate_state.rate_state_source,
+            Pubkey::new_from_array([7; 32])
+        );
+        assert_eq!(
+            rate_state.sampling_data.twap().unwrap(),
+            ([dec!(2); 10], 20, 0)
+        );
+
+        // migrating twice is a no-op
+        assert!(migrate_v1_data(&migrated).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_migrate_current_data() {
+        let mut rate_state = RateState {
+            fair_value: [[0; 16]; 10],
+            refreshed_slot: 0,
+            refreshed_unix_timestamp: 0,
+            rate_state_source: Pubkey::default(),
+            sampling_data: SamplingData::new(0, 3).unwrap(),
+        };
+        rate_state
+            .sampling_data
+            .try_add([Decimal::ONE; 10], 5, 50)
+            .unwrap();
+        rate_state.compute_twap().unwrap();
+
+        let mut data = Vec::new();
+        rate_state.try_serialize(&mut data).unwrap();
+        data.resize(RateState::len(3), 0);
+
+        assert!(migrate_v1_data(&data).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_migrate_unknown_data() {
+        let mut data = v1_data(4, &[(dec!(1), 10)]);
+        data.pop();
+        assert!(migrate_v1_data(&data).is_err());
+    }
+}
@@ -1,5 +1,8 @@
 pub mod initialize;
 pub use initialize::*;
 
+pub mod migrate;
+pub use migrate::*;
+
 pub mod refresh;
 pub use refresh::*;
@@ -28,6 +28,7 @@ pub fn handler(ctx: Context<RefreshRateContext>) -> Result<()> {
     rate_state.sampling_data.try_add(
         rate_state_source.fair_value.map(Decimal::deserialize),
         rate_state_source.refreshed_slot,
+        rate_state_source.refreshed_unix_timestamp,
     )?;
 
     rate_state.compute_twap()?;
@@ -43,6 +44,10 @@ pub fn handler(ctx: Context<RefreshRateContext>) -> Result<()> {
             "rate_state refreshed_slot: {:#?}",
             rate_state.refreshed_slot
         );
+        msg!(
+            "rate_state refreshed_unix_timestamp: {:#?}",
+            rate_state.refreshed_unix_timestamp
+        );
     }
 
     Ok(())
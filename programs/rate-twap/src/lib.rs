@@ -29,4 +29,8 @@ pub mod rate_twap {
     pub fn refresh(ctx: Context<RefreshRateContext>) -> Result<()> {
         instructions::refresh::handler(ctx)
     }
+
+    pub fn migrate(ctx: Context<MigrateContext>) -> Result<()> {
+        instructions::migrate::handler(ctx)
+    }
 }
@@ -6,16 +6,19 @@ use super::SamplingData;
 pub struct RateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
     pub rate_state_source: Pubkey,
     pub sampling_data: SamplingData,
 }
 
 impl RateState {
     pub fn compute_twap(&mut self) -> Result<()> {
-        let (twap_value, twap_refreshed_slot) = self.sampling_data.twap()?;
+        let (twap_value, twap_refreshed_slot, twap_refreshed_unix_timestamp) =
+            self.sampling_data.twap()?;
 
         self.fair_value = twap_value.map(|c| c.serialize());
         self.refreshed_slot = twap_refreshed_slot;
+        self.refreshed_unix_timestamp = twap_refreshed_unix_timestamp;
 
         Ok(())
     }
@@ -24,6 +27,7 @@ impl RateState {
         8 + // discriminator
             10*16 + // pub fair_value: [[u8; 16]; 10],
             8 + // pub refreshed_slot: u64,
+            8 + // pub refreshed_unix_timestamp: i64,
             32 + // pub rate_state_source: Pubkey,
             SamplingData::len(sampling_size) // pub sampling_data: SamplingData
     }
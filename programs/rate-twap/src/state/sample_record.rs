@@ -6,19 +6,22 @@ use std::fmt;
 pub struct SampleRecord {
     value: [[u8; 16]; 10],
     slot: u64,
+    unix_timestamp: i64,
 }
 
 impl SampleRecord {
 
     pub const LEN: usize = 
         10 * 16 + // value: [[u8; 16]; 10],
-        8 // slot: u64,
+        8 + // slot: u64,
+        8 // unix_timestamp: i64,
         ;
 
-    pub fn new(value: [Decimal; 10], slot: u64) -> SampleRecord {
+    pub fn new(value: [Decimal; 10], slot: u64, unix_timestamp: i64) -> SampleRecord {
         SampleRecord {
             value: value.map(|f| f.serialize()),
             slot,
+            unix_timestamp,
         }
     }
 
@@ -29,6 +32,10 @@ impl SampleRecord {
     pub fn get_slot(&self) -> u64 {
         self.slot
     }
+
+    pub fn get_unix_timestamp(&self) -> i64 {
+        self.unix_timestamp
+    }
 }
 
 impl fmt::Debug for SampleRecord {
@@ -36,6 +43,7 @@ impl fmt::Debug for SampleRecord {
         f.debug_struct("SampleRecord")
          .field("value", &self.get_value())
          .field("slot", &self.get_slot())
+         .field("unix_timestamp", &self.get_unix_timestamp())
          .finish()
     }
 }
@@ -48,9 +56,10 @@ mod tests {
 
     #[test]
     fn test_getters() {
-        let v = SampleRecord::new([dec!(10); 10], 10);
+        let v = SampleRecord::new([dec!(10); 10], 10, 100);
 
         assert_eq!(v.get_value(), [dec!(10); 10]);
         assert_eq!(v.get_slot(), 10);
+        assert_eq!(v.get_unix_timestamp(), 100);
     }
 }
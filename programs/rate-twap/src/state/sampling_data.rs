@@ -36,7 +36,7 @@ impl SamplingData {
     /// will try to add a new sample
     /// this can fail if there's already another sample with a close slot as the new one
     /// the threshold used for checking is the self.min_slot_delta
-    pub fn try_add(&mut self, value: [Decimal; 10], slot: u64) -> Result<()> {
+    pub fn try_add(&mut self, value: [Decimal; 10], slot: u64, unix_timestamp: i64) -> Result<()> {
         if !self.samples.is_empty() {
             // check if there's a too recent samples
             if (slot - self.samples[self.get_most_recent_sample_idx()].get_slot())
@@ -52,17 +52,22 @@ impl SamplingData {
             }
         }
 
-        self.samples.push(SampleRecord::new(value, slot));
+        self.samples
+            .push(SampleRecord::new(value, slot, unix_timestamp));
         Ok(())
     }
 
     /// Get the twap
-    /// Average of all the values and the most recent slot
-    pub fn twap(&self) -> Result<([Decimal; 10], u64)> {
+    /// Average of all the values, the most recent slot and its unix timestamp
+    pub fn twap(&self) -> Result<([Decimal; 10], u64, i64)> {
         let avg = self.avg()?;
-        let most_recent_slot = self.samples[self.get_most_recent_sample_idx()].get_slot();
+        let most_recent_sample = &self.samples[self.get_most_recent_sample_idx()];
 
-        Ok((avg, most_recent_slot))
+        Ok((
+            avg,
+            most_recent_sample.get_slot(),
+            most_recent_sample.get_unix_timestamp(),
+        ))
     }
 
     /// Get the average of all the samples value
@@ -126,27 +131,27 @@ mod tests {
     fn test_try_add() {
         let mut sampling = SamplingData::new(0, 4).unwrap();
 
-        sampling.try_add([Decimal::ZERO; 10], 0).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 1).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 2).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 3).unwrap();
-        assert_eq!(sampling.twap().unwrap(), ([Decimal::ZERO; 10], 3));
+        sampling.try_add([Decimal::ZERO; 10], 0, 0).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 1, 10).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 2, 20).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 3, 30).unwrap();
+        assert_eq!(sampling.twap().unwrap(), ([Decimal::ZERO; 10], 3, 30));
 
-        sampling.try_add([Decimal::ZERO; 10], 4).unwrap();
-        assert_eq!(sampling.twap().unwrap(), ([Decimal::ZERO; 10], 4));
+        sampling.try_add([Decimal::ZERO; 10], 4, 40).unwrap();
+        assert_eq!(sampling.twap().unwrap(), ([Decimal::ZERO; 10], 4, 40));
     }
 
     #[test]
     fn test_avg() {
         let mut sampling = SamplingData::new(0, 4).unwrap();
 
-        sampling.try_add([Decimal::ZERO; 10], 0).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 1).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 2).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 3).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 0, 0).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 1, 10).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 2, 20).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 3, 30).unwrap();
         assert_eq!(sampling.avg().unwrap(), [Decimal::ZERO; 10]);
 
-        sampling.try_add([Decimal::ONE; 10], 4).unwrap();
+        sampling.try_add([Decimal::ONE; 10], 4, 40).unwrap();
         assert_eq!(sampling.avg().unwrap(), [dec!(0.25); 10]);
     }
 
@@ -154,19 +159,19 @@ mod tests {
     fn test_error_on_recent_slot() {
         let mut sampling = SamplingData::new(2, 4).unwrap();
 
-        sampling.try_add([Decimal::ZERO; 10], 0).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 2).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 4).unwrap();
-        assert!(sampling.try_add([Decimal::ZERO; 10], 5).is_err());
+        sampling.try_add([Decimal::ZERO; 10], 0, 0).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 2, 20).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 4, 40).unwrap();
+        assert!(sampling.try_add([Decimal::ZERO; 10], 5, 50).is_err());
     }
 
     #[test]
     fn test_get_most_recent_sample_idx() {
         let mut sampling = SamplingData::new(2, 4).unwrap();
 
-        sampling.try_add([Decimal::ZERO; 10], 0).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 2).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 4).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 0, 0).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 2, 20).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 4, 40).unwrap();
         assert_eq!(sampling.get_most_recent_sample_idx(), 2);
     }
 
@@ -174,9 +179,9 @@ mod tests {
     fn test_get_oldest_sample_idx() {
         let mut sampling = SamplingData::new(2, 4).unwrap();
 
-        sampling.try_add([Decimal::ZERO; 10], 0).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 2).unwrap();
-        sampling.try_add([Decimal::ZERO; 10], 4).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 0, 0).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 2, 20).unwrap();
+        sampling.try_add([Decimal::ZERO; 10], 4, 40).unwrap();
         assert_eq!(sampling.get_oldest_sample_idx(), 0);
     }
 }
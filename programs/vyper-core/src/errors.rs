@@ -37,4 +37,100 @@ pub enum VyperErrorCode {
 
     #[msg("current deposit exceeded cap")]
     DepositExceededCap,
+
+    #[msg("signer is not the pending owner of the tranche config")]
+    InvalidPendingOwner,
+
+    #[msg("queued change can't be applied before the timelock expires")]
+    TimelockNotExpired,
+
+    #[msg("loosening changes must be queued and wait for the timelock")]
+    TimelockRequired,
+
+    #[msg("signer is not the guardian of the tranche config")]
+    InvalidGuardian,
+
+    #[msg("tranche can't be closed while it holds deposits, tranche tokens or fees to collect")]
+    TrancheNotEmpty,
+
+    #[msg("fees are distributed through the fee split")]
+    FeeSplitEnabled,
+
+    #[msg("fee recipient account doesn't match the fee split")]
+    InvalidFeeRecipient,
+
+    #[msg("Current operation is not available because is halted at protocol level")]
+    ProtocolHaltError,
+
+    #[msg("tranche config version is not supported, the account needs to be migrated")]
+    UnsupportedTrancheConfigVersion,
+
+    #[msg("output quantity is below the requested minimum")]
+    SlippageExceeded,
+
+    #[msg("invalid allowlist restricted ix flags")]
+    InvalidAllowlistRestrictedIxFlags,
+
+    #[msg("signer is not in the tranche allowlist")]
+    NotAllowlisted,
+
+    #[msg("signer is not allowed to manage the tranche allowlist")]
+    InvalidAllowlistAuthority,
+
+    #[msg("deposit exceeds the wallet cap")]
+    WalletDepositExceededCap,
+
+    #[msg("user position account is required when wallet deposit caps are enabled")]
+    MissingUserPosition,
+
+    #[msg("deposit exceeds the total cap")]
+    TotalDepositExceededCap,
+
+    #[msg("invalid coverage ratio bounds, min above max")]
+    InvalidCoverageRatioBounds,
+
+    #[msg("senior junior coverage ratio out of bounds")]
+    CoverageRatioOutOfBounds,
+
+    #[msg("invalid lifecycle, timestamps must be ordered as subscription start, subscription end, maturity")]
+    InvalidLifecycle,
+
+    #[msg("tranche subscription not started yet")]
+    SubscriptionNotStarted,
+
+    #[msg("tranche subscription ended")]
+    SubscriptionEnded,
+
+    #[msg("tranche maturity not reached yet")]
+    TrancheNotMatured,
+
+    #[msg("tranche already settled, fair values are final")]
+    TrancheSettled,
+
+    #[msg("tranche not settled yet")]
+    TrancheNotSettled,
+
+    #[msg("queued request already processed")]
+    QueuedRequestAlreadyProcessed,
+
+    #[msg("tranche fair value not refreshed after the queued request")]
+    QueuedRequestNotRefreshed,
+
+    #[msg("first deposit below the minimum initial deposit")]
+    MinInitialDepositNotReached,
+
+    #[msg("missing dead shares token account")]
+    MissingDeadSharesAccount,
+
+    #[msg("queued requests still outstanding")]
+    QueuedRequestsOutstanding,
+
+    #[msg("tranche fair value refreshed after the queued request, it can't be cancelled")]
+    QueuedRequestNotCancellable,
+
+    #[msg("stale tranche fair value, the rate and redeem logic plugin accounts are required to refresh it")]
+    MissingPluginAccounts,
+
+    #[msg("missing pending update, fee split or epoch roll account of the tranche")]
+    MissingTrancheAccount,
 }
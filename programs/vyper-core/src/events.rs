@@ -0,0 +1,500 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::UpdateTrancheDataInput,
+    state::{
+        DepositCapMode, FeeRecipient, OrphanedQuantityDestination, QueuedRequestKind,
+        ReserveSurplusDestination, StalenessMode, TrancheLifecycle,
+    },
+};
+
+#[event]
+pub struct PluginUpdateQueued {
+    pub tranche_config: Pubkey,
+    pub rate_program: Pubkey,
+    pub rate_program_state: Pubkey,
+    pub redeem_logic_program: Pubkey,
+    pub redeem_logic_program_state: Pubkey,
+    pub executable_slot: u64,
+}
+
+#[event]
+pub struct PluginUpdateApplied {
+    pub tranche_config: Pubkey,
+    pub rate_program: Pubkey,
+    pub rate_program_state: Pubkey,
+    pub redeem_logic_program: Pubkey,
+    pub redeem_logic_program_state: Pubkey,
+}
+
+#[event]
+pub struct PluginUpdateCancelled {
+    pub tranche_config: Pubkey,
+}
+
+#[event]
+pub struct TrancheDataUpdateQueued {
+    pub tranche_config: Pubkey,
+    pub update: UpdateTrancheDataInput,
+    pub executable_slot: u64,
+}
+
+#[event]
+pub struct TrancheDataUpdateApplied {
+    pub tranche_config: Pubkey,
+    pub bitmask: u16,
+    pub bitmask_ext: u16,
+}
+
+#[event]
+pub struct TrancheDataUpdateCancelled {
+    pub tranche_config: Pubkey,
+}
+
+#[event]
+pub struct TrancheInitialized {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub owner: Pubkey,
+    pub reserve_mint: Pubkey,
+    pub senior_tranche_mint: Pubkey,
+    pub junior_tranche_mint: Pubkey,
+    pub rate_program: Pubkey,
+    pub rate_program_state: Pubkey,
+    pub redeem_logic_program: Pubkey,
+    pub redeem_logic_program_state: Pubkey,
+    pub reserve_mint_decimals: u8,
+    pub tranche_mint_decimals: u8,
+    pub version: [u8; 3],
+}
+
+#[event]
+pub struct Deposited {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    /// reserve quantity transferred in, fees included
+    pub reserve_quantity: [u64; 2],
+    pub entry_fee_quantity: [u64; 2],
+    pub minted_quantity: [u64; 2],
+    pub tranche_fair_value: [[u8; 16]; 2],
+    pub deposited_quantity: [u64; 2],
+}
+
+#[event]
+pub struct Redeemed {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub burned_quantity: [u64; 2],
+    /// reserve quantity transferred out, fees excluded
+    pub reserve_quantity: u64,
+    pub exit_fee_quantity: [u64; 2],
+    pub tranche_fair_value: [[u8; 16]; 2],
+    pub deposited_quantity: [u64; 2],
+}
+
+#[event]
+pub struct TrancheFairValueRefreshed {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_reserve_fair_value: [[u8; 16]; 10],
+    pub new_reserve_fair_value: [[u8; 16]; 10],
+    pub old_tranche_fair_value: [[u8; 16]; 2],
+    pub new_tranche_fair_value: [[u8; 16]; 2],
+    pub old_deposited_quantity: [u64; 2],
+    pub new_deposited_quantity: [u64; 2],
+    pub fee_quantity: u64,
+}
+
+#[event]
+pub struct FeeCollected {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub fee_quantity: u64,
+    pub reserve_surplus: u64,
+}
+
+#[event]
+pub struct HaltFlagsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u16,
+    pub new_value: u16,
+}
+
+#[event]
+pub struct OwnerRestrictedIxsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u16,
+    pub new_value: u16,
+}
+
+#[event]
+pub struct ReserveFairValueStaleSlotThresholdUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct TrancheFairValueStaleSlotThresholdUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct DepositCapUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: [Option<u64>; 2],
+    pub new_value: [Option<u64>; 2],
+}
+
+#[event]
+pub struct EntryFeeBpsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: [u16; 2],
+    pub new_value: [u16; 2],
+}
+
+#[event]
+pub struct ExitFeeBpsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: [u16; 2],
+    pub new_value: [u16; 2],
+}
+
+#[event]
+pub struct TimelockSlotsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: Option<Pubkey>,
+    pub new_value: Option<Pubkey>,
+}
+
+#[event]
+pub struct ReserveSurplusDestinationUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: ReserveSurplusDestination,
+    pub new_value: ReserveSurplusDestination,
+}
+
+#[event]
+pub struct AllowlistRestrictedIxsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u16,
+    pub new_value: u16,
+}
+
+#[event]
+pub struct AllowlistAdminUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: Option<Pubkey>,
+    pub new_value: Option<Pubkey>,
+}
+
+#[event]
+pub struct WalletDepositCapUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: [Option<u64>; 2],
+    pub new_value: [Option<u64>; 2],
+}
+
+#[event]
+pub struct DepositCapModeUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: DepositCapMode,
+    pub new_value: DepositCapMode,
+}
+
+#[event]
+pub struct TotalDepositCapUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: Option<u64>,
+    pub new_value: Option<u64>,
+}
+
+#[event]
+pub struct CoverageRatioBpsBoundsUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: [Option<u64>; 2],
+    pub new_value: [Option<u64>; 2],
+}
+
+#[event]
+pub struct LifecycleUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: TrancheLifecycle,
+    pub new_value: TrancheLifecycle,
+}
+
+#[event]
+pub struct TrancheSettled {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub reserve_fair_value: [[u8; 16]; 10],
+    pub tranche_fair_value: [[u8; 16]; 2],
+    pub deposited_quantity: [u64; 2],
+    pub settled_at: i64,
+}
+
+#[event]
+pub struct EpochRollQueued {
+    pub tranche_config: Pubkey,
+    pub redeem_logic_program_state: Pubkey,
+    pub lifecycle: TrancheLifecycle,
+    pub executable_slot: u64,
+}
+
+#[event]
+pub struct EpochRollCancelled {
+    pub tranche_config: Pubkey,
+}
+
+#[event]
+pub struct EpochRolled {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub epoch: u64,
+    pub old_redeem_logic_program_state: Pubkey,
+    pub new_redeem_logic_program_state: Pubkey,
+    pub lifecycle: TrancheLifecycle,
+}
+
+#[event]
+pub struct QueuedRequestCreated {
+    pub tranche_config: Pubkey,
+    pub user: Pubkey,
+    pub kind: QueuedRequestKind,
+    pub quantity: [u64; 2],
+    pub requested_slot: u64,
+}
+
+#[event]
+pub struct QueuedRequestProcessed {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub user: Pubkey,
+    pub kind: QueuedRequestKind,
+    pub tranche_quantity: [u64; 2],
+    pub reserve_quantity: [u64; 2],
+    pub fee_quantity: [u64; 2],
+    pub tranche_fair_value: [[u8; 16]; 2],
+    pub deposited_quantity: [u64; 2],
+}
+
+#[event]
+pub struct QueuedRequestRejected {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub user: Pubkey,
+    pub kind: QueuedRequestKind,
+    pub tranche_quantity: [u64; 2],
+    pub reserve_quantity: [u64; 2],
+}
+
+#[event]
+pub struct QueuedRequestClaimed {
+    pub tranche_config: Pubkey,
+    pub user: Pubkey,
+    pub kind: QueuedRequestKind,
+    pub cancelled: bool,
+    pub tranche_quantity: [u64; 2],
+    pub reserve_quantity: u64,
+}
+
+#[event]
+pub struct ReserveFairValueStaleSecondsThresholdUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct TrancheFairValueStaleSecondsThresholdUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct ReserveFairValueStalenessModeUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: StalenessMode,
+    pub new_value: StalenessMode,
+}
+
+#[event]
+pub struct TrancheFairValueStalenessModeUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: StalenessMode,
+    pub new_value: StalenessMode,
+}
+
+#[event]
+pub struct OrphanedQuantityRouted {
+    pub tranche_config: Pubkey,
+    pub destination: OrphanedQuantityDestination,
+    pub orphaned_quantity: [u64; 2],
+}
+
+#[event]
+pub struct OrphanedQuantityDestinationUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: OrphanedQuantityDestination,
+    pub new_value: OrphanedQuantityDestination,
+}
+
+#[event]
+pub struct DeadSharesLocked {
+    pub tranche_config: Pubkey,
+    pub dead_shares_quantity: [u64; 2],
+}
+
+#[event]
+pub struct MinInitialDepositUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: [u64; 2],
+    pub new_value: [u64; 2],
+}
+
+#[event]
+pub struct DeadSharesQuantityUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+}
+
+#[event]
+pub struct OwnerProposed {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+#[event]
+pub struct OwnerAccepted {
+    pub tranche_config: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct TrancheClosed {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub reserve_dust_quantity: u64,
+}
+
+#[event]
+pub struct ProtocolFeeCollected {
+    pub tranche_config: Pubkey,
+    pub protocol_config: Pubkey,
+    pub fee_quantity: u64,
+}
+
+#[event]
+pub struct ProtocolAdminUpdated {
+    pub protocol_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: Pubkey,
+    pub new_value: Pubkey,
+}
+
+#[event]
+pub struct ProtocolFeeBpsUpdated {
+    pub protocol_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u16,
+    pub new_value: u16,
+}
+
+#[event]
+pub struct ProtocolTreasuryUpdated {
+    pub protocol_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: Pubkey,
+    pub new_value: Pubkey,
+}
+
+#[event]
+pub struct ProtocolHaltFlagsUpdated {
+    pub protocol_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_value: u16,
+    pub new_value: u16,
+}
+
+#[event]
+pub struct AllowlistEntryAdded {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub user: Pubkey,
+}
+
+#[event]
+pub struct AllowlistEntryRemoved {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub user: Pubkey,
+}
+
+#[event]
+pub struct UserDepositCapUpdated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub user: Pubkey,
+    pub old_value: Option<[Option<u64>; 2]>,
+    pub new_value: Option<[Option<u64>; 2]>,
+}
+
+#[event]
+pub struct TrancheConfigMigrated {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub old_version: [u8; 3],
+    pub new_version: [u8; 3],
+}
+
+#[event]
+pub struct FeeSplitInitialized {
+    pub tranche_config: Pubkey,
+    pub signer: Pubkey,
+    pub recipients: Vec<FeeRecipient>,
+}
+
+#[event]
+pub struct FeeSplitCollected {
+    pub tranche_config: Pubkey,
+    pub fee_quantity: u64,
+    pub recipient_quantity: Vec<u64>,
+    pub reserve_surplus: u64,
+}
@@ -0,0 +1,39 @@
+use crate::{errors::VyperErrorCode, events::OwnerAccepted, state::TrancheConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptOwnerContext<'info> {
+    /// Pending owner, the signature confirms the ownership transfer
+    pub new_owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut,
+        constraint = tranche_config.pending_owner == Some(new_owner.key()) @ VyperErrorCode::InvalidPendingOwner,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+}
+
+pub fn handler(ctx: Context<AcceptOwnerContext>) -> Result<()> {
+    let tranche_config = &mut ctx.accounts.tranche_config;
+
+    msg!("accept tranche_config ownership");
+
+    let old_owner = tranche_config.owner;
+
+    #[cfg(feature = "debug")]
+    msg!("+ old value: {}", tranche_config.owner);
+
+    tranche_config.owner = ctx.accounts.new_owner.key();
+    tranche_config.pending_owner = None;
+
+    #[cfg(feature = "debug")]
+    msg!("+ new value: {}", tranche_config.owner);
+
+    emit!(OwnerAccepted {
+        tranche_config: tranche_config.key(),
+        old_owner,
+        new_owner: tranche_config.owner,
+    });
+
+    Ok(())
+}
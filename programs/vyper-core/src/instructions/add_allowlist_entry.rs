@@ -0,0 +1,47 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::AllowlistEntryAdded,
+    state::{AllowlistEntry, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AddAllowlistEntryContext<'info> {
+    /// Tranche config owner or allowlist admin
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(
+        constraint = tranche_config.is_allowlist_authority(authority.key()) @ VyperErrorCode::InvalidAllowlistAuthority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK: User to allowlist
+    #[account()]
+    pub user: AccountInfo<'info>,
+
+    /// Allowlist entry, one per user and tranche config
+    #[account(init, payer = authority, space = AllowlistEntry::LEN, seeds = [tranche_config.key().as_ref(), b"allowlist".as_ref(), user.key().as_ref()], bump)]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddAllowlistEntryContext>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    msg!("add {} to the allowlist", ctx.accounts.user.key());
+    let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+    allowlist_entry.tranche_config = ctx.accounts.tranche_config.key();
+    allowlist_entry.user = ctx.accounts.user.key();
+    allowlist_entry.created_at = clock.unix_timestamp;
+
+    emit!(AllowlistEntryAdded {
+        tranche_config: allowlist_entry.tranche_config,
+        signer: ctx.accounts.authority.key(),
+        user: allowlist_entry.user,
+    });
+
+    Ok(())
+}
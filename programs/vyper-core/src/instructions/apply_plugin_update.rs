@@ -0,0 +1,75 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::PluginUpdateApplied,
+    state::{PendingPluginUpdate, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApplyPluginUpdateContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut, has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued plugin update, closed once applied
+    #[account(mut,
+        has_one = tranche_config,
+        close = owner,
+        seeds = [tranche_config.key().as_ref(), b"pending_plugin_update".as_ref()],
+        bump)]
+    pub pending_plugin_update: Box<Account<'info, PendingPluginUpdate>>,
+}
+
+pub fn handler(ctx: Context<ApplyPluginUpdateContext>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pending_plugin_update = &ctx.accounts.pending_plugin_update;
+
+    // check that the timelock expired
+    if clock.slot < pending_plugin_update.executable_slot {
+        msg!(
+            "plugin update executable from slot {}, current slot {}",
+            pending_plugin_update.executable_slot,
+            clock.slot
+        );
+        return err!(VyperErrorCode::TimelockNotExpired);
+    }
+
+    msg!("apply plugin update");
+    let tranche_config = &mut ctx.accounts.tranche_config;
+
+    #[cfg(feature = "debug")]
+    msg!(
+        "+ old value: rate {} {} redeem logic {} {}",
+        tranche_config.rate_program,
+        tranche_config.rate_program_state,
+        tranche_config.redeem_logic_program,
+        tranche_config.redeem_logic_program_state
+    );
+
+    tranche_config.rate_program = pending_plugin_update.rate_program;
+    tranche_config.rate_program_state = pending_plugin_update.rate_program_state;
+    tranche_config.redeem_logic_program = pending_plugin_update.redeem_logic_program;
+    tranche_config.redeem_logic_program_state = pending_plugin_update.redeem_logic_program_state;
+
+    #[cfg(feature = "debug")]
+    msg!(
+        "+ new value: rate {} {} redeem logic {} {}",
+        tranche_config.rate_program,
+        tranche_config.rate_program_state,
+        tranche_config.redeem_logic_program,
+        tranche_config.redeem_logic_program_state
+    );
+
+    emit!(PluginUpdateApplied {
+        tranche_config: tranche_config.key(),
+        rate_program: tranche_config.rate_program,
+        rate_program_state: tranche_config.rate_program_state,
+        redeem_logic_program: tranche_config.redeem_logic_program,
+        redeem_logic_program_state: tranche_config.redeem_logic_program_state,
+    });
+
+    Ok(())
+}
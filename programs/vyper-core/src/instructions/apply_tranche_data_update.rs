@@ -0,0 +1,55 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::TrancheDataUpdateApplied,
+    instructions::update_tranche_config,
+    state::{PendingTrancheDataUpdate, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApplyTrancheDataUpdateContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut, has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued tranche data update, closed once applied
+    #[account(mut,
+        has_one = tranche_config,
+        close = owner,
+        seeds = [tranche_config.key().as_ref(), b"pending_tranche_data_update".as_ref()],
+        bump)]
+    pub pending_tranche_data_update: Box<Account<'info, PendingTrancheDataUpdate>>,
+}
+
+pub fn handler(ctx: Context<ApplyTrancheDataUpdateContext>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pending_tranche_data_update = &ctx.accounts.pending_tranche_data_update;
+
+    // check that the timelock expired
+    if clock.slot < pending_tranche_data_update.executable_slot {
+        msg!(
+            "tranche data update executable from slot {}, current slot {}",
+            pending_tranche_data_update.executable_slot,
+            clock.slot
+        );
+        return err!(VyperErrorCode::TimelockNotExpired);
+    }
+
+    msg!("apply tranche data update");
+    update_tranche_config(
+        &mut ctx.accounts.tranche_config,
+        ctx.accounts.owner.key(),
+        &pending_tranche_data_update.update,
+    )?;
+
+    emit!(TrancheDataUpdateApplied {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        bitmask: pending_tranche_data_update.update.bitmask,
+        bitmask_ext: pending_tranche_data_update.update.bitmask_ext,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::EpochRollCancelled,
+    state::{PendingEpochRoll, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelEpochRollContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued epoch roll to discard
+    #[account(mut,
+        has_one = tranche_config,
+        close = owner,
+        seeds = [tranche_config.key().as_ref(), b"pending_epoch_roll".as_ref()],
+        bump)]
+    pub pending_epoch_roll: Box<Account<'info, PendingEpochRoll>>,
+}
+
+pub fn handler(ctx: Context<CancelEpochRollContext>) -> Result<()> {
+    msg!("cancel epoch roll");
+
+    emit!(EpochRollCancelled {
+        tranche_config: ctx.accounts.tranche_config.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::PluginUpdateCancelled,
+    state::{PendingPluginUpdate, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelPluginUpdateContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued plugin update to discard
+    #[account(mut,
+        has_one = tranche_config,
+        close = owner,
+        seeds = [tranche_config.key().as_ref(), b"pending_plugin_update".as_ref()],
+        bump)]
+    pub pending_plugin_update: Box<Account<'info, PendingPluginUpdate>>,
+}
+
+pub fn handler(ctx: Context<CancelPluginUpdateContext>) -> Result<()> {
+    msg!("cancel plugin update");
+
+    emit!(PluginUpdateCancelled {
+        tranche_config: ctx.accounts.tranche_config.key(),
+    });
+
+    Ok(())
+}
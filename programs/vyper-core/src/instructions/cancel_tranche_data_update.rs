@@ -0,0 +1,34 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::TrancheDataUpdateCancelled,
+    state::{PendingTrancheDataUpdate, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelTrancheDataUpdateContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued tranche data update to discard
+    #[account(mut,
+        has_one = tranche_config,
+        close = owner,
+        seeds = [tranche_config.key().as_ref(), b"pending_tranche_data_update".as_ref()],
+        bump)]
+    pub pending_tranche_data_update: Box<Account<'info, PendingTrancheDataUpdate>>,
+}
+
+pub fn handler(ctx: Context<CancelTrancheDataUpdateContext>) -> Result<()> {
+    msg!("cancel tranche data update");
+
+    emit!(TrancheDataUpdateCancelled {
+        tranche_config: ctx.accounts.tranche_config.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::QueuedRequestClaimed,
+    state::{QueuedRequest, QueuedRequestKind, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+use boolinator::Boolinator;
+
+#[derive(Accounts)]
+pub struct ClaimQueuedRequestContext<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        has_one = reserve,
+        has_one = tranche_authority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// Queued request to claim, closed once claimed
+    #[account(mut,
+        has_one = tranche_config,
+        constraint = queued_request.user == signer.key() @ VyperErrorCode::InvalidInput,
+        close = signer,
+        seeds = [tranche_config.key().as_ref(), b"queued_request".as_ref(), signer.key().as_ref()],
+        bump)]
+    pub queued_request: Box<Account<'info, QueuedRequest>>,
+
+    /// tranche reserve vault
+    #[account(mut)]
+    pub reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = tranche_config.reserve_mint)]
+    pub user_reserve_token: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = tranche_config.senior_tranche_mint)]
+    pub senior_tranche_dest: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = tranche_config.junior_tranche_mint)]
+    pub junior_tranche_dest: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [queued_request.key().as_ref(), b"senior_escrow".as_ref()], bump)]
+    pub senior_tranche_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [queued_request.key().as_ref(), b"junior_escrow".as_ref()], bump)]
+    pub junior_tranche_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimQueuedRequestContext<'info> {
+    fn are_valid(&self) -> Result<()> {
+        // an unprocessed request can be cancelled only until its execution fair value is known
+        if !self.queued_request.processed {
+            let last_update_slot = self
+                .tranche_config
+                .tranche_data
+                .tranche_fair_value
+                .slot_tracking
+                .get_last_update_slot();
+            (!self.tranche_config.settled
+                && last_update_slot <= self.queued_request.requested_slot)
+                .ok_or(VyperErrorCode::QueuedRequestNotCancellable)?;
+        }
+
+        Result::Ok(())
+    }
+
+    /// CpiContext for transferring tokens held by the tranche authority to the user
+    fn transfer_context(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: self.tranche_authority.clone(),
+            },
+        )
+    }
+
+    /// CpiContext for closing a request escrow
+    fn close_context(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: escrow.to_account_info(),
+                destination: self.signer.to_account_info(),
+                authority: self.tranche_authority.clone(),
+            },
+        )
+    }
+}
+
+/// Claim the outputs of a processed request, an unprocessed request is cancelled and the locked tokens returned
+pub fn handler(ctx: Context<ClaimQueuedRequestContext>) -> Result<()> {
+    // check if accounts are valid
+    msg!("check if accounts are valid");
+    ctx.accounts.are_valid()?;
+
+    let queued_request = &ctx.accounts.queued_request;
+
+    // reserve owed to the user: redeemed reserve, the reserve of a rejected deposit or the reserve locked by a cancelled deposit
+    let reserve_quantity = match (queued_request.kind, queued_request.processed) {
+        (_, true) => queued_request.reserve_to_claim_quantity,
+        (QueuedRequestKind::Deposit, false) => queued_request.quantity.iter().sum::<u64>(),
+        (QueuedRequestKind::Redeem, false) => 0,
+    };
+    ctx.accounts.tranche_config.queued_reserve_quantity = ctx
+        .accounts
+        .tranche_config
+        .queued_reserve_quantity
+        .checked_sub(reserve_quantity)
+        .ok_or(VyperErrorCode::MathError)?;
+
+    let authority_seeds = ctx.accounts.tranche_config.authority_seeds();
+    if reserve_quantity > 0 {
+        msg!("transfer out {} reserve", reserve_quantity);
+        token::transfer(
+            ctx.accounts
+                .transfer_context(&ctx.accounts.reserve, &ctx.accounts.user_reserve_token)
+                .with_signer(&[&authority_seeds]),
+            reserve_quantity,
+        )?;
+    }
+
+    // tranches held by the escrows: minted tranches or the tranches locked by a cancelled redeem
+    let escrows = [
+        (
+            &ctx.accounts.senior_tranche_escrow,
+            &ctx.accounts.senior_tranche_dest,
+        ),
+        (
+            &ctx.accounts.junior_tranche_escrow,
+            &ctx.accounts.junior_tranche_dest,
+        ),
+    ];
+    let mut tranche_quantity: [u64; 2] = [0; 2];
+    for (tranche_qty, (escrow, dest)) in tranche_quantity.iter_mut().zip(escrows) {
+        *tranche_qty = escrow.amount;
+        if *tranche_qty > 0 {
+            msg!("transfer out {} tranches", *tranche_qty);
+            token::transfer(
+                ctx.accounts
+                    .transfer_context(escrow, dest)
+                    .with_signer(&[&authority_seeds]),
+                *tranche_qty,
+            )?;
+        }
+        token::close_account(
+            ctx.accounts
+                .close_context(escrow)
+                .with_signer(&[&authority_seeds]),
+        )?;
+    }
+
+    let queued_request = &ctx.accounts.queued_request;
+    emit!(QueuedRequestClaimed {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        user: queued_request.user,
+        kind: queued_request.kind,
+        cancelled: !queued_request.processed,
+        tranche_quantity,
+        reserve_quantity,
+    });
+
+    Ok(())
+}
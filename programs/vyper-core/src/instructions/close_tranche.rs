@@ -0,0 +1,309 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::TrancheClosed,
+    instructions::find_dead_shares_account,
+    state::{
+        FeeSplit, PendingEpochRoll, PendingPluginUpdate, PendingTrancheDataUpdate, TrancheConfig,
+    },
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{
+    self, spl_token::instruction::AuthorityType, Burn, CloseAccount, Mint, SetAuthority, Token,
+    TokenAccount, Transfer,
+};
+
+#[derive(Accounts)]
+pub struct CloseTrancheContext<'info> {
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, closed at the end of the instruction
+    #[account(mut,
+        has_one = owner,
+        has_one = tranche_authority,
+        has_one = reserve,
+        has_one = senior_tranche_mint,
+        has_one = junior_tranche_mint,
+        close = destination,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// tranche reserve vault, closed at the end of the instruction
+    #[account(mut)]
+    pub reserve: Box<Account<'info, TokenAccount>>,
+
+    /// destination of the reserve tokens left in the vault
+    #[account(mut, token::mint = tranche_config.reserve_mint)]
+    pub dest_reserve: Box<Account<'info, TokenAccount>>,
+
+    /// Senior tranche mint
+    #[account(mut)]
+    pub senior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// Junior tranche mint
+    #[account(mut)]
+    pub junior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Destination of the reclaimed rent
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CloseTrancheContext<'info> {
+    fn are_valid(&self, dead_shares_quantity: [u64; 2]) -> Result<()> {
+        let tranche_data = &self.tranche_config.tranche_data;
+
+        // check that the tranche is fully wound down, the locked dead shares and the deposited quantity backing them excluded
+        let tranche_supply = [
+            self.senior_tranche_mint.supply,
+            self.junior_tranche_mint.supply,
+        ];
+        let is_side_empty = |i: usize| {
+            tranche_supply[i] == dead_shares_quantity[i]
+                && (dead_shares_quantity[i] > 0 || tranche_data.deposited_quantity[i] == 0)
+        };
+        if !is_side_empty(0)
+            || !is_side_empty(1)
+            || tranche_data.fee_to_collect_quantity > 0
+            || self.tranche_config.protocol_fee_to_collect_quantity > 0
+        {
+            return err!(VyperErrorCode::TrancheNotEmpty);
+        }
+
+        // queued deposits lock reserve and processed queued redeems owe reserve until claimed,
+        // the tranches of the other queued requests sit in escrow and count in the mint supply
+        if self.tranche_config.queued_reserve_quantity > 0 {
+            return err!(VyperErrorCode::QueuedRequestsOutstanding);
+        }
+
+        Result::Ok(())
+    }
+
+    /// CpiContext for transferring the reserve dust out of the vault
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve.to_account_info(),
+                to: self.dest_reserve.to_account_info(),
+                authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+
+    /// CpiContext for closing the reserve vault
+    fn close_reserve_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.reserve.to_account_info(),
+                destination: self.destination.to_account_info(),
+                authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+
+    /// CpiContext for burning the dead shares
+    fn burn_dead_shares_context(
+        &self,
+        mint: &Account<'info, Mint>,
+        dead_shares: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Burn {
+                mint: mint.to_account_info(),
+                from: dead_shares.to_account_info(),
+                authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+
+    /// CpiContext for closing a dead shares token account
+    fn close_dead_shares_context(
+        &self,
+        dead_shares: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: dead_shares.to_account_info(),
+                destination: self.destination.to_account_info(),
+                authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+
+    /// CpiContext for revoking the tranche mint authority
+    fn revoke_mint_authority_context(
+        &self,
+        mint: &Account<'info, Mint>,
+    ) -> CpiContext<'_, '_, '_, 'info, SetAuthority<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            SetAuthority {
+                account_or_mint: mint.to_account_info(),
+                current_authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseTrancheContext<'info>>) -> Result<()> {
+    // the dead shares token accounts, if created, are passed as remaining accounts
+    let mut dead_shares: [Option<Account<'info, TokenAccount>>; 2] = [None, None];
+    let tranche_mints = [
+        ctx.accounts.senior_tranche_mint.key(),
+        ctx.accounts.junior_tranche_mint.key(),
+    ];
+    for (dead_shares_account, tranche_mint) in dead_shares.iter_mut().zip(tranche_mints) {
+        if let Some(account_info) = find_dead_shares_account(
+            ctx.accounts.tranche_config.key(),
+            tranche_mint,
+            ctx.remaining_accounts,
+        ) {
+            *dead_shares_account = Some(Account::<TokenAccount>::try_from(account_info)?);
+        }
+    }
+    let dead_shares_quantity =
+        [&dead_shares[0], &dead_shares[1]].map(|account| account.as_ref().map_or(0, |a| a.amount));
+
+    // check if accounts are valid
+    msg!("check if accounts are valid");
+    ctx.accounts.are_valid(dead_shares_quantity)?;
+
+    let authority_seeds = ctx.accounts.tranche_config.authority_seeds();
+
+    // rounding leftovers and the reserve backing the dead shares can't be claimed by anyone, send them out before closing the vault
+    let reserve_dust_quantity = ctx.accounts.reserve.amount;
+    if reserve_dust_quantity > 0 {
+        msg!("transfer out reserve dust: {}", ctx.accounts.reserve.amount);
+        token::transfer(
+            ctx.accounts
+                .transfer_context()
+                .with_signer(&[&authority_seeds]),
+            ctx.accounts.reserve.amount,
+        )?;
+    }
+
+    msg!("close reserve");
+    token::close_account(
+        ctx.accounts
+            .close_reserve_context()
+            .with_signer(&[&authority_seeds]),
+    )?;
+
+    // burn the dead shares and close their token accounts
+    for (dead_shares_account, mint) in dead_shares.iter().zip([
+        &ctx.accounts.senior_tranche_mint,
+        &ctx.accounts.junior_tranche_mint,
+    ]) {
+        if let Some(dead_shares_account) = dead_shares_account {
+            if dead_shares_account.amount > 0 {
+                msg!(
+                    "burn {} dead shares of {}",
+                    dead_shares_account.amount,
+                    mint.key()
+                );
+                token::burn(
+                    ctx.accounts
+                        .burn_dead_shares_context(mint, dead_shares_account)
+                        .with_signer(&[&authority_seeds]),
+                    dead_shares_account.amount,
+                )?;
+            }
+            token::close_account(
+                ctx.accounts
+                    .close_dead_shares_context(dead_shares_account)
+                    .with_signer(&[&authority_seeds]),
+            )?;
+        }
+    }
+
+    // close the accounts derived from the tranche config, they would outlive it
+    let tranche_config_key = ctx.accounts.tranche_config.key();
+    let destination = &ctx.accounts.destination;
+    close_tranche_account::<PendingPluginUpdate>(
+        tranche_config_key,
+        b"pending_plugin_update",
+        ctx.remaining_accounts,
+        destination,
+    )?;
+    close_tranche_account::<PendingTrancheDataUpdate>(
+        tranche_config_key,
+        b"pending_tranche_data_update",
+        ctx.remaining_accounts,
+        destination,
+    )?;
+    close_tranche_account::<FeeSplit>(
+        tranche_config_key,
+        b"fee_split",
+        ctx.remaining_accounts,
+        destination,
+    )?;
+    close_tranche_account::<PendingEpochRoll>(
+        tranche_config_key,
+        b"pending_epoch_roll",
+        ctx.remaining_accounts,
+        destination,
+    )?;
+
+    // spl token mints can't be closed, remove the mint authority so that no more tranches can be issued
+    msg!("revoke senior tranche mint authority");
+    token::set_authority(
+        ctx.accounts
+            .revoke_mint_authority_context(&ctx.accounts.senior_tranche_mint)
+            .with_signer(&[&authority_seeds]),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    msg!("revoke junior tranche mint authority");
+    token::set_authority(
+        ctx.accounts
+            .revoke_mint_authority_context(&ctx.accounts.junior_tranche_mint)
+            .with_signer(&[&authority_seeds]),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    msg!("close tranche config");
+
+    emit!(TrancheClosed {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        reserve_dust_quantity,
+    });
+
+    Ok(())
+}
+
+/// Close the account derived from the tranche config with the given seed, passed as remaining account.
+/// The account is required even if it has not been created, so that it can't be left behind
+fn close_tranche_account<'info, T>(
+    tranche_config: Pubkey,
+    seed: &[u8],
+    remaining_accounts: &[AccountInfo<'info>],
+    destination: &AccountInfo<'info>,
+) -> Result<()>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let (account_key, _) =
+        Pubkey::find_program_address(&[tranche_config.as_ref(), seed], &crate::ID);
+    let account = remaining_accounts
+        .iter()
+        .find(|account| account.key() == account_key && account.is_writable)
+        .ok_or(VyperErrorCode::MissingTrancheAccount)?;
+    if *account.owner != crate::ID {
+        return Ok(());
+    }
+
+    msg!("close {}", account_key);
+    Account::<T>::try_from(account)?.close(destination.clone())
+}
@@ -1,4 +1,4 @@
-use crate::state::TrancheConfig;
+use crate::{errors::VyperErrorCode, events::FeeCollected, state::TrancheConfig};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
@@ -8,7 +8,9 @@ pub struct CollectFeeContext<'info> {
     #[account(mut,
         has_one = owner,
         has_one = tranche_authority,
-        has_one = reserve)]
+        has_one = reserve,
+        constraint = !tranche_config.has_fee_split @ VyperErrorCode::FeeSplitEnabled,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
     pub tranche_config: Box<Account<'info, TrancheConfig>>,
 
     /// CHECK:
@@ -43,7 +45,16 @@ impl<'info> CollectFeeContext<'info> {
 }
 
 pub fn handler(ctx: Context<CollectFeeContext>) -> Result<()> {
-    // TODO calculate tranching rounding and collect in fee the exceding part
+    // reconcile the reserve vault with the tracked quantities
+    let reserve_surplus = ctx
+        .accounts
+        .tranche_config
+        .reconcile_reserve_surplus(ctx.accounts.reserve.amount)?;
+    msg!(
+        "reserve surplus: {}, destination: {:?}",
+        reserve_surplus,
+        ctx.accounts.tranche_config.reserve_surplus_destination
+    );
 
     let fee_to_collect_quantity = ctx
         .accounts
@@ -63,5 +74,12 @@ pub fn handler(ctx: Context<CollectFeeContext>) -> Result<()> {
         .tranche_data
         .fee_to_collect_quantity = 0;
 
+    emit!(FeeCollected {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        fee_quantity: fee_to_collect_quantity,
+        reserve_surplus,
+    });
+
     Ok(())
 }
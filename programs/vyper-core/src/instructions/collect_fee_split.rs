@@ -0,0 +1,139 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::FeeSplitCollected,
+    state::{FeeSplit, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct CollectFeeSplitContext<'info> {
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut,
+        has_one = tranche_authority,
+        has_one = reserve,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// tranche reserve vault
+    #[account(mut)]
+    pub reserve: Box<Account<'info, TokenAccount>>,
+
+    /// Fee recipients, their token accounts are passed as remaining accounts in the same order
+    #[account(has_one = tranche_config,
+        seeds = [tranche_config.key().as_ref(), b"fee_split".as_ref()],
+        bump)]
+    pub fee_split: Box<Account<'info, FeeSplit>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CollectFeeSplitContext<'info> {
+    /// CpiContext for transferring reserve tokens from vault to a fee recipient
+    fn transfer_context(
+        &self,
+        recipient: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve.to_account_info(),
+                to: recipient,
+                authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CollectFeeSplitContext<'info>>,
+) -> Result<()> {
+    let recipients = &ctx.accounts.fee_split.recipients;
+    if ctx.remaining_accounts.len() != recipients.len() {
+        msg!("expected {} fee recipient accounts", recipients.len());
+        return err!(VyperErrorCode::InvalidFeeRecipient);
+    }
+
+    // reconcile the reserve vault with the tracked quantities, collect_fee is disabled with a fee split
+    let reserve_surplus = ctx
+        .accounts
+        .tranche_config
+        .reconcile_reserve_surplus(ctx.accounts.reserve.amount)?;
+    msg!(
+        "reserve surplus: {}, destination: {:?}",
+        reserve_surplus,
+        ctx.accounts.tranche_config.reserve_surplus_destination
+    );
+
+    let fee_to_collect_quantity = ctx
+        .accounts
+        .tranche_config
+        .tranche_data
+        .fee_to_collect_quantity;
+    msg!("collecting fee: {}", fee_to_collect_quantity);
+
+    // the last recipient takes the rounding leftovers
+    let mut distributed_quantity = 0u64;
+    let mut recipient_quantity_list = Vec::with_capacity(recipients.len());
+    for (i, (recipient, recipient_account)) in recipients
+        .iter()
+        .zip(ctx.remaining_accounts.iter())
+        .enumerate()
+    {
+        require_keys_eq!(
+            recipient.token_account,
+            recipient_account.key(),
+            VyperErrorCode::InvalidFeeRecipient
+        );
+
+        let recipient_quantity = if i == recipients.len() - 1 {
+            fee_to_collect_quantity
+                .checked_sub(distributed_quantity)
+                .ok_or(VyperErrorCode::MathError)?
+        } else {
+            u64::try_from(
+                (fee_to_collect_quantity as u128)
+                    .checked_mul(recipient.weight_bps as u128)
+                    .ok_or(VyperErrorCode::MathError)?
+                    / 10_000,
+            )
+            .map_err(|_| VyperErrorCode::MathError)?
+        };
+        distributed_quantity = distributed_quantity
+            .checked_add(recipient_quantity)
+            .ok_or(VyperErrorCode::MathError)?;
+        recipient_quantity_list.push(recipient_quantity);
+
+        if recipient_quantity > 0 {
+            msg!(
+                "transfer {} to {}",
+                recipient_quantity,
+                recipient.token_account
+            );
+            token::transfer(
+                ctx.accounts
+                    .transfer_context(recipient_account.clone())
+                    .with_signer(&[&ctx.accounts.tranche_config.authority_seeds()]),
+                recipient_quantity,
+            )?;
+        }
+    }
+
+    ctx.accounts
+        .tranche_config
+        .tranche_data
+        .fee_to_collect_quantity = 0;
+
+    emit!(FeeSplitCollected {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        fee_quantity: fee_to_collect_quantity,
+        recipient_quantity: recipient_quantity_list,
+        reserve_surplus,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::ProtocolFeeCollected,
+    state::{ProtocolConfig, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct CollectProtocolFeeContext<'info> {
+    /// Protocol config account, a single PDA for the whole program
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut,
+        has_one = tranche_authority,
+        has_one = reserve,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// tranche reserve vault
+    #[account(mut)]
+    pub reserve: Box<Account<'info, TokenAccount>>,
+
+    /// protocol treasury token account
+    #[account(mut,
+        token::mint = tranche_config.reserve_mint,
+        token::authority = protocol_config.treasury)]
+    pub treasury_reserve: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CollectProtocolFeeContext<'info> {
+    /// CpiContext for transferring reserve tokens from vault to the protocol treasury
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve.to_account_info(),
+                to: self.treasury_reserve.to_account_info(),
+                authority: self.tranche_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<CollectProtocolFeeContext>) -> Result<()> {
+    let protocol_fee_to_collect_quantity =
+        ctx.accounts.tranche_config.protocol_fee_to_collect_quantity;
+    msg!(
+        "collecting protocol fee: {}",
+        protocol_fee_to_collect_quantity
+    );
+    token::transfer(
+        ctx.accounts
+            .transfer_context()
+            .with_signer(&[&ctx.accounts.tranche_config.authority_seeds()]),
+        protocol_fee_to_collect_quantity,
+    )?;
+
+    ctx.accounts.tranche_config.protocol_fee_to_collect_quantity = 0;
+
+    emit!(ProtocolFeeCollected {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        protocol_config: ctx.accounts.protocol_config.key(),
+        fee_quantity: protocol_fee_to_collect_quantity,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::QueuedRequestCreated,
+    state::{
+        AllowlistEntry, AllowlistRestrictedIxFlags, OwnerRestrictedIxFlags, ProtocolConfig,
+        QueuedRequest, QueuedRequestKind, TrancheConfig, TrancheHaltFlags,
+    },
+    utils::Input,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use boolinator::Boolinator;
+
+#[derive(Accounts)]
+pub struct CreateQueuedRequestContext<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        has_one = junior_tranche_mint,
+        has_one = senior_tranche_mint,
+        has_one = reserve,
+        has_one = tranche_authority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Protocol config account, checked for protocol level halts
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// Queued request, one per wallet and tranche config
+    #[account(init, payer = signer, space = QueuedRequest::LEN, seeds = [tranche_config.key().as_ref(), b"queued_request".as_ref(), signer.key().as_ref()], bump)]
+    pub queued_request: Box<Account<'info, QueuedRequest>>,
+
+    /// tranche reserve vault, holding the reserve locked by queued deposits
+    #[account(mut)]
+    pub reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = tranche_config.reserve_mint)]
+    pub user_reserve_token: Box<Account<'info, TokenAccount>>,
+
+    /// Senior tranche mint
+    pub senior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// Junior tranche mint
+    pub junior_tranche_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, token::mint = tranche_config.senior_tranche_mint)]
+    pub senior_tranche_source: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = tranche_config.junior_tranche_mint)]
+    pub junior_tranche_source: Box<Account<'info, TokenAccount>>,
+
+    /// Senior tranches locked by the request or minted to the request
+    #[account(init, payer = signer, seeds = [queued_request.key().as_ref(), b"senior_escrow".as_ref()], bump, token::mint = senior_tranche_mint, token::authority = tranche_authority)]
+    pub senior_tranche_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Junior tranches locked by the request or minted to the request
+    #[account(init, payer = signer, seeds = [queued_request.key().as_ref(), b"junior_escrow".as_ref()], bump, token::mint = junior_tranche_mint, token::authority = tranche_authority)]
+    pub junior_tranche_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateQueuedRequestContext<'info> {
+    fn are_valid(&self, kind: QueuedRequestKind) -> Result<()> {
+        let tranche_data = &self.tranche_config.tranche_data;
+        let (halt_flag, owner_restricted_ix) = match kind {
+            QueuedRequestKind::Deposit => (
+                TrancheHaltFlags::HALT_DEPOSITS,
+                OwnerRestrictedIxFlags::DEPOSITS,
+            ),
+            QueuedRequestKind::Redeem => (
+                TrancheHaltFlags::HALT_REDEEMS,
+                OwnerRestrictedIxFlags::REDEEMS,
+            ),
+        };
+
+        // check that the requested action is not halted
+        (!tranche_data.get_halt_flags()?.contains(halt_flag)).ok_or(VyperErrorCode::HaltError)?;
+
+        // check that the requested action is not halted at protocol level
+        (!self.protocol_config.get_halt_flags()?.contains(halt_flag))
+            .ok_or(VyperErrorCode::ProtocolHaltError)?;
+
+        // check that the requested action is allowed in the current lifecycle phase
+        let unix_timestamp = Clock::get()?.unix_timestamp;
+        match kind {
+            QueuedRequestKind::Deposit => self
+                .tranche_config
+                .lifecycle
+                .check_deposit_allowed(unix_timestamp)?,
+            QueuedRequestKind::Redeem => self
+                .tranche_config
+                .lifecycle
+                .check_redeem_allowed(unix_timestamp)?,
+        }
+
+        // check if the requested action is restricted to owner
+        if tranche_data
+            .get_owner_restricted_ixs()?
+            .contains(owner_restricted_ix)
+        {
+            require_keys_eq!(
+                self.tranche_config.owner,
+                self.signer.key(),
+                VyperErrorCode::OwnerRestrictedIx
+            )
+        }
+
+        Result::Ok(())
+    }
+
+    /// CpiContext for transferring tokens from the user to the vaults
+    fn transfer_context(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: self.signer.to_account_info(),
+            },
+        )
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
+pub struct CreateQueuedRequestInput {
+    pub kind: QueuedRequestKind,
+
+    /// reserve quantity to deposit or tranche quantity to redeem, for senior and junior side
+    pub quantity: [u64; 2],
+
+    /// minimum senior and junior tranches minted by a deposit
+    pub min_tranche_out: [Option<u64>; 2],
+
+    /// minimum reserve received by a redeem, net of exit fees
+    pub min_reserve_out: Option<u64>,
+}
+
+impl Input for CreateQueuedRequestInput {
+    fn is_valid(&self) -> Result<()> {
+        if self.quantity.iter().sum::<u64>() == 0 {
+            msg!("quantity must me greater than zero");
+            return err!(VyperErrorCode::InvalidInput);
+        }
+
+        Result::Ok(())
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateQueuedRequestContext<'info>>,
+    input_data: CreateQueuedRequestInput,
+) -> Result<()> {
+    // check if accounts are valid
+    msg!("check if accounts are valid");
+    ctx.accounts.are_valid(input_data.kind)?;
+
+    // check input
+    msg!("check if input is valid");
+    input_data.is_valid()?;
+
+    // check if the requested action is restricted to allowlisted users
+    let allowlist_restricted_ix = match input_data.kind {
+        QueuedRequestKind::Deposit => AllowlistRestrictedIxFlags::DEPOSITS,
+        QueuedRequestKind::Redeem => AllowlistRestrictedIxFlags::REDEEMS,
+    };
+    if ctx
+        .accounts
+        .tranche_config
+        .get_allowlist_restricted_ixs()?
+        .contains(allowlist_restricted_ix)
+    {
+        AllowlistEntry::is_allowlisted(
+            ctx.remaining_accounts,
+            ctx.accounts.tranche_config.key(),
+            ctx.accounts.signer.key(),
+        )
+        .ok_or(VyperErrorCode::NotAllowlisted)?;
+    }
+
+    // lock the tokens
+    match input_data.kind {
+        QueuedRequestKind::Deposit => {
+            let reserve_quantity = input_data.quantity.iter().sum::<u64>();
+            msg!("lock {} reserve", reserve_quantity);
+            token::transfer(
+                ctx.accounts
+                    .transfer_context(&ctx.accounts.user_reserve_token, &ctx.accounts.reserve),
+                reserve_quantity,
+            )?;
+            ctx.accounts.tranche_config.queued_reserve_quantity = ctx
+                .accounts
+                .tranche_config
+                .queued_reserve_quantity
+                .checked_add(reserve_quantity)
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+        QueuedRequestKind::Redeem => {
+            if input_data.quantity[0] > 0 {
+                msg!("lock {} senior tranches", input_data.quantity[0]);
+                token::transfer(
+                    ctx.accounts.transfer_context(
+                        &ctx.accounts.senior_tranche_source,
+                        &ctx.accounts.senior_tranche_escrow,
+                    ),
+                    input_data.quantity[0],
+                )?;
+            }
+            if input_data.quantity[1] > 0 {
+                msg!("lock {} junior tranches", input_data.quantity[1]);
+                token::transfer(
+                    ctx.accounts.transfer_context(
+                        &ctx.accounts.junior_tranche_source,
+                        &ctx.accounts.junior_tranche_escrow,
+                    ),
+                    input_data.quantity[1],
+                )?;
+            }
+        }
+    }
+
+    let queued_request = &mut ctx.accounts.queued_request;
+    queued_request.tranche_config = ctx.accounts.tranche_config.key();
+    queued_request.user = ctx.accounts.signer.key();
+    queued_request.kind = input_data.kind;
+    queued_request.quantity = input_data.quantity;
+    queued_request.requested_slot = Clock::get()?.slot;
+    queued_request.processed = false;
+    queued_request.reserve_to_claim_quantity = 0;
+    queued_request.min_tranche_out = input_data.min_tranche_out;
+    queued_request.min_reserve_out = input_data.min_reserve_out;
+
+    emit!(QueuedRequestCreated {
+        tranche_config: queued_request.tranche_config,
+        user: queued_request.user,
+        kind: queued_request.kind,
+        quantity: queued_request.quantity,
+        requested_slot: queued_request.requested_slot,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,11 @@
 use crate::{
     errors::VyperErrorCode,
-    state::{OwnerRestrictedIxFlags, TrancheConfig, TrancheHaltFlags},
+    events::{DeadSharesLocked, Deposited},
+    instructions::{mint_dead_shares, refresh_if_stale},
+    state::{
+        AllowlistEntry, AllowlistRestrictedIxFlags, OwnerRestrictedIxFlags, ProtocolConfig,
+        TrancheConfig, TrancheHaltFlags, UserPosition,
+    },
     utils::Input,
 };
 use anchor_lang::prelude::*;
@@ -17,9 +22,14 @@ pub struct DepositContext<'info> {
         has_one = junior_tranche_mint,
         has_one = senior_tranche_mint,
         has_one = reserve,
-        has_one = tranche_authority)]
+        has_one = tranche_authority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
     pub tranche_config: Box<Account<'info, TrancheConfig>>,
 
+    /// Protocol config account, checked for protocol level halts
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
     /// CHECK:
     #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
     pub tranche_authority: AccountInfo<'info>,
@@ -53,7 +63,6 @@ pub struct DepositContext<'info> {
 
 impl<'info> DepositContext<'info> {
     fn are_valid(&self) -> Result<()> {
-        let clock = Clock::get()?;
         let tranche_data = &self.tranche_config.tranche_data;
 
         // check that deposits are not halted
@@ -62,12 +71,17 @@ impl<'info> DepositContext<'info> {
             .contains(TrancheHaltFlags::HALT_DEPOSITS))
         .ok_or(VyperErrorCode::HaltError)?;
 
-        // check that tranche fair values are not halted
-        (!tranche_data
-            .tranche_fair_value
-            .slot_tracking
-            .is_stale(clock.slot)?)
-        .ok_or(VyperErrorCode::StaleFairValue)?;
+        // check that deposits are not halted at protocol level
+        (!self
+            .protocol_config
+            .get_halt_flags()?
+            .contains(TrancheHaltFlags::HALT_DEPOSITS))
+        .ok_or(VyperErrorCode::ProtocolHaltError)?;
+
+        // check that deposits are allowed in the current lifecycle phase
+        self.tranche_config
+            .lifecycle
+            .check_deposit_allowed(Clock::get()?.unix_timestamp)?;
 
         // check if the current ix is restricted to owner
         if tranche_data
@@ -124,6 +138,9 @@ impl<'info> DepositContext<'info> {
 #[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
 pub struct DepositInput {
     pub reserve_quantity: [u64; 2],
+
+    /// minimum senior and junior tranches to mint, None disables the check
+    pub min_tranche_out: [Option<u64>; 2],
 }
 
 impl Input for DepositInput {
@@ -137,7 +154,10 @@ impl Input for DepositInput {
     }
 }
 
-pub fn handler(ctx: Context<DepositContext>, input_data: DepositInput) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositContext<'info>>,
+    input_data: DepositInput,
+) -> Result<()> {
     // check if accounts are valid
     msg!("check if accounts are valid");
     ctx.accounts.are_valid()?;
@@ -145,26 +165,93 @@ pub fn handler(ctx: Context<DepositContext>, input_data: DepositInput) -> Result
     // check if input is valid
     msg!("check if input is valid");
     input_data.is_valid()?;
-    
-    let tranche_data = &mut ctx.accounts.tranche_config.tranche_data;
 
-    // check if deposits exceeded cap
-    msg!("check deposit cap");
+    // check if the current ix is restricted to allowlisted users
+    if ctx
+        .accounts
+        .tranche_config
+        .get_allowlist_restricted_ixs()?
+        .contains(AllowlistRestrictedIxFlags::DEPOSITS)
+    {
+        AllowlistEntry::is_allowlisted(
+            ctx.remaining_accounts,
+            ctx.accounts.tranche_config.key(),
+            ctx.accounts.signer.key(),
+        )
+        .ok_or(VyperErrorCode::NotAllowlisted)?;
+    }
+
+    // refresh the tranche fair value if stale, the plugin accounts are passed as remaining accounts
+    let tranche_supply = [
+        ctx.accounts.senior_tranche_mint.supply,
+        ctx.accounts.junior_tranche_mint.supply,
+    ];
+    refresh_if_stale(
+        &mut ctx.accounts.tranche_config,
+        &ctx.accounts.protocol_config,
+        tranche_supply,
+        ctx.accounts.signer.key(),
+        ctx.remaining_accounts,
+    )?;
+
+    // compute entry fees
+    let mut entry_fee_quantity: [u64; 2] = [0; 2];
+    let mut net_reserve_quantity: [u64; 2] = [0; 2];
     for i in 0..input_data.reserve_quantity.len() {
-        if let Some(deposit_cap) = tranche_data.deposit_cap[i] {
-            if tranche_data.deposited_quantity[i] + input_data.reserve_quantity[i] > deposit_cap {
-                return err!(VyperErrorCode::DepositExceededCap);
-            }
-        }    
+        entry_fee_quantity[i] = ctx
+            .accounts
+            .tranche_config
+            .tranche_data
+            .get_entry_fee(i, input_data.reserve_quantity[i])?;
+        net_reserve_quantity[i] = input_data.reserve_quantity[i]
+            .checked_sub(entry_fee_quantity[i])
+            .ok_or(VyperErrorCode::MathError)?;
     }
+    msg!("entry fee: {:?}", entry_fee_quantity);
+    ctx.accounts.tranche_config.add_fee(
+        entry_fee_quantity.iter().sum::<u64>(),
+        &ctx.accounts.protocol_config,
+    )?;
+
+    // check if deposits exceeded the wallet cap, the user position is passed as remaining account
+    if ctx.accounts.tranche_config.has_wallet_deposit_cap() {
+        msg!("check wallet deposit cap");
+        let mut user_position = UserPosition::load(
+            ctx.remaining_accounts,
+            ctx.accounts.tranche_config.key(),
+            ctx.accounts.signer.key(),
+        )?
+        .ok_or(VyperErrorCode::MissingUserPosition)?;
+        let wallet_deposit_cap = ctx
+            .accounts
+            .tranche_config
+            .get_wallet_deposit_cap(&user_position);
+        user_position.add_deposit(net_reserve_quantity, wallet_deposit_cap)?;
+        user_position.exit(ctx.program_id)?;
+    }
+
+    // check if deposits exceeded cap
+    msg!("check deposit cap");
+    ctx.accounts
+        .tranche_config
+        .check_deposit_cap(net_reserve_quantity)?;
 
     // increase deposited_quantity
-    for i in 0..input_data.reserve_quantity.len() {
+    let tranche_data = &mut ctx.accounts.tranche_config.tranche_data;
+    let old_deposited_quantity = tranche_data.deposited_quantity;
+    for (i, &net_reserve_qty) in net_reserve_quantity.iter().enumerate() {
         tranche_data.deposited_quantity[i] = tranche_data.deposited_quantity[i]
-            .checked_add(input_data.reserve_quantity[i])
+            .checked_add(net_reserve_qty)
             .ok_or(VyperErrorCode::MathError)?;
     }
 
+    // check that the deposit keeps the senior junior ratio within the bounds
+    msg!("check coverage ratio");
+    ctx.accounts.tranche_config.check_coverage_ratio(
+        old_deposited_quantity,
+        ctx.accounts.tranche_config.tranche_data.deposited_quantity,
+    )?;
+
     // transfer token from source account to tranche config token account
     token::transfer(
         ctx.accounts.transfer_context(),
@@ -173,6 +260,7 @@ pub fn handler(ctx: Context<DepositContext>, input_data: DepositInput) -> Result
 
     // mint tranches
 
+    let decimals_scale = ctx.accounts.tranche_config.get_decimals_scale()?;
     let mut mint_count: [u64; 2] = [0; 2];
     for (i, mc) in mint_count.iter_mut().enumerate() {
         let tranche_fv = Decimal::deserialize(ctx.accounts
@@ -180,17 +268,76 @@ pub fn handler(ctx: Context<DepositContext>, input_data: DepositInput) -> Result
                 .tranche_data
                 .tranche_fair_value
                 .value[i]);
-        let dep_qty = Decimal::from(input_data.reserve_quantity[i]);
+        let dep_qty = Decimal::from(net_reserve_quantity[i]);
 
         msg!("tranche_fv: {}", tranche_fv);
         msg!("dep_qty: {}", dep_qty);
 
-        *mc = (dep_qty / tranche_fv)
+        *mc = dep_qty
+            .checked_div(
+                tranche_fv
+                    .checked_mul(decimals_scale)
+                    .ok_or(VyperErrorCode::MathError)?,
+            )
+            .ok_or(VyperErrorCode::MathError)?
             .floor()
             .to_u64()
             .ok_or(VyperErrorCode::MathError)?;
     }
 
+    // lock the dead shares of the first deposit of a side, out of the depositor tranches
+    let mut dead_shares_count: [u64; 2] = [0; 2];
+    for (i, (mc, dsc)) in mint_count
+        .iter_mut()
+        .zip(dead_shares_count.iter_mut())
+        .enumerate()
+    {
+        *dsc = ctx.accounts.tranche_config.get_initial_dead_shares(
+            i,
+            net_reserve_quantity[i],
+            tranche_supply[i],
+        )?;
+        *mc = mc
+            .checked_sub(*dsc)
+            .ok_or(VyperErrorCode::MinInitialDepositNotReached)?;
+    }
+
+    // check slippage
+    for (&mc, &min_tranche_out) in mint_count.iter().zip(input_data.min_tranche_out.iter()) {
+        if let Some(min_tranche_out) = min_tranche_out {
+            if mc < min_tranche_out {
+                msg!(
+                    "minted tranches {} below the minimum {}",
+                    mc,
+                    min_tranche_out
+                );
+                return err!(VyperErrorCode::SlippageExceeded);
+            }
+        }
+    }
+
+    if dead_shares_count[0] > 0 {
+        mint_dead_shares(
+            &ctx.accounts.tranche_config,
+            &ctx.accounts.senior_tranche_mint,
+            &ctx.accounts.tranche_authority,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            dead_shares_count[0],
+        )?;
+    }
+
+    if dead_shares_count[1] > 0 {
+        mint_dead_shares(
+            &ctx.accounts.tranche_config,
+            &ctx.accounts.junior_tranche_mint,
+            &ctx.accounts.tranche_authority,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            dead_shares_count[1],
+        )?;
+    }
+
     if mint_count[0] > 0 {
         msg!("mint {} senior tranches", mint_count[0]);
         token::mint_to(
@@ -211,5 +358,23 @@ pub fn handler(ctx: Context<DepositContext>, input_data: DepositInput) -> Result
         )?;
     }
 
+    if dead_shares_count.iter().any(|&dsc| dsc > 0) {
+        emit!(DeadSharesLocked {
+            tranche_config: ctx.accounts.tranche_config.key(),
+            dead_shares_quantity: dead_shares_count,
+        });
+    }
+
+    let tranche_data = &ctx.accounts.tranche_config.tranche_data;
+    emit!(Deposited {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.signer.key(),
+        reserve_quantity: input_data.reserve_quantity,
+        entry_fee_quantity,
+        minted_quantity: mint_count,
+        tranche_fair_value: tranche_data.tranche_fair_value.value,
+        deposited_quantity: tranche_data.deposited_quantity,
+    });
+
     Ok(())
 }
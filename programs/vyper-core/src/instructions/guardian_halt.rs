@@ -0,0 +1,47 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::HaltFlagsUpdated,
+    state::{TrancheConfig, TrancheHaltFlags},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GuardianHaltContext<'info> {
+    pub guardian: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut,
+        constraint = tranche_config.guardian == Some(guardian.key()) @ VyperErrorCode::InvalidGuardian,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+}
+
+pub fn handler(ctx: Context<GuardianHaltContext>, halt_flags: u16) -> Result<()> {
+    let tranche_config_key = ctx.accounts.tranche_config.key();
+    let tranche_data = &mut ctx.accounts.tranche_config.tranche_data;
+
+    // the guardian can only add halt flags, current ones are always preserved
+    let added_halt_flags =
+        TrancheHaltFlags::from_bits(halt_flags).ok_or(VyperErrorCode::InvalidTrancheHaltFlags)?;
+
+    msg!("guardian add tranche_data halt_flags");
+
+    let old_value = tranche_data.get_halt_flags()?.bits();
+
+    #[cfg(feature = "debug")]
+    msg!("+ old value: {}", tranche_data.get_halt_flags()?.bits());
+
+    tranche_data.set_halt_flags((tranche_data.get_halt_flags()? | added_halt_flags).bits())?;
+
+    #[cfg(feature = "debug")]
+    msg!("+ new value: {}", tranche_data.get_halt_flags()?.bits());
+
+    emit!(HaltFlagsUpdated {
+        tranche_config: tranche_config_key,
+        signer: ctx.accounts.guardian.key(),
+        old_value,
+        new_value: tranche_data.get_halt_flags()?.bits(),
+    });
+
+    Ok(())
+}
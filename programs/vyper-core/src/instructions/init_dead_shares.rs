@@ -0,0 +1,100 @@
+use crate::{errors::VyperErrorCode, state::TrancheConfig};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitDeadSharesContext<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(
+        has_one = senior_tranche_mint,
+        has_one = junior_tranche_mint,
+        has_one = tranche_authority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// Senior tranche mint
+    pub senior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// Junior tranche mint
+    pub junior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// Senior tranches locked by the first senior deposit
+    #[account(init, payer = payer, seeds = [tranche_config.key().as_ref(), b"dead_shares".as_ref(), senior_tranche_mint.key().as_ref()], bump, token::mint = senior_tranche_mint, token::authority = tranche_authority)]
+    pub senior_tranche_dead_shares: Box<Account<'info, TokenAccount>>,
+
+    /// Junior tranches locked by the first junior deposit
+    #[account(init, payer = payer, seeds = [tranche_config.key().as_ref(), b"dead_shares".as_ref(), junior_tranche_mint.key().as_ref()], bump, token::mint = junior_tranche_mint, token::authority = tranche_authority)]
+    pub junior_tranche_dead_shares: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<InitDeadSharesContext>) -> Result<()> {
+    msg!(
+        "create dead shares token accounts for {}",
+        ctx.accounts.tranche_config.key()
+    );
+
+    Ok(())
+}
+
+/// Find the writable dead shares token account of a tranche mint in the remaining accounts
+pub fn find_dead_shares_account<'a, 'info>(
+    tranche_config: Pubkey,
+    tranche_mint: Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Option<&'a AccountInfo<'info>> {
+    let (dead_shares_key, _) = Pubkey::find_program_address(
+        &[
+            tranche_config.as_ref(),
+            b"dead_shares".as_ref(),
+            tranche_mint.as_ref(),
+        ],
+        &crate::ID,
+    );
+    remaining_accounts
+        .iter()
+        .find(|account| account.key() == dead_shares_key && account.is_writable)
+}
+
+/// Mint the dead shares of a first deposit to the tranche authority,
+/// the dead shares token account of the tranche mint is passed as remaining account
+pub fn mint_dead_shares<'info>(
+    tranche_config: &Account<'info, TrancheConfig>,
+    tranche_mint: &Account<'info, Mint>,
+    tranche_authority: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    remaining_accounts: &[AccountInfo<'info>],
+    dead_shares_quantity: u64,
+) -> Result<()> {
+    let dead_shares =
+        find_dead_shares_account(tranche_config.key(), tranche_mint.key(), remaining_accounts)
+            .ok_or(VyperErrorCode::MissingDeadSharesAccount)?;
+
+    msg!(
+        "lock {} dead shares of {}",
+        dead_shares_quantity,
+        tranche_mint.key()
+    );
+    token::mint_to(
+        CpiContext::new(
+            token_program.to_account_info(),
+            MintTo {
+                mint: tranche_mint.to_account_info(),
+                to: dead_shares.clone(),
+                authority: tranche_authority.clone(),
+            },
+        )
+        .with_signer(&[&tranche_config.authority_seeds()]),
+        dead_shares_quantity,
+    )
+}
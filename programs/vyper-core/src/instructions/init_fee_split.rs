@@ -0,0 +1,112 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::FeeSplitInitialized,
+    state::{FeeRecipient, FeeSplit, TrancheConfig},
+    utils::Input,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+pub struct InitFeeSplitContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut, has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Fee recipients of the tranche config, their token accounts are passed as remaining accounts in the same order
+    #[account(init, payer = owner, space = FeeSplit::LEN, seeds = [tranche_config.key().as_ref(), b"fee_split".as_ref()], bump)]
+    pub fee_split: Box<Account<'info, FeeSplit>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
+pub struct InitFeeSplitInput {
+    pub recipients: Vec<FeeRecipient>,
+}
+
+impl Input for InitFeeSplitInput {
+    fn is_valid(&self) -> Result<()> {
+        if self.recipients.is_empty() || self.recipients.len() > FeeSplit::MAX_RECIPIENTS {
+            msg!(
+                "recipients must be between 1 and {}",
+                FeeSplit::MAX_RECIPIENTS
+            );
+            return err!(VyperErrorCode::InvalidInput);
+        }
+
+        if self.recipients.iter().any(|r| r.weight_bps == 0) {
+            msg!("recipient weights must be greater than zero");
+            return err!(VyperErrorCode::InvalidInput);
+        }
+
+        if self
+            .recipients
+            .iter()
+            .map(|r| r.weight_bps as u64)
+            .sum::<u64>()
+            != 10_000
+        {
+            msg!("recipient weights must sum up to 10_000 bps");
+            return err!(VyperErrorCode::InvalidInput);
+        }
+
+        Result::Ok(())
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitFeeSplitContext<'info>>,
+    input_data: InitFeeSplitInput,
+) -> Result<()> {
+    // check if input is valid
+    msg!("check if input is valid");
+    input_data.is_valid()?;
+
+    // check that the recipients are reserve token accounts
+    if ctx.remaining_accounts.len() != input_data.recipients.len() {
+        msg!(
+            "expected {} fee recipient accounts",
+            input_data.recipients.len()
+        );
+        return err!(VyperErrorCode::InvalidFeeRecipient);
+    }
+    for (recipient, recipient_account) in input_data
+        .recipients
+        .iter()
+        .zip(ctx.remaining_accounts.iter())
+    {
+        require_keys_eq!(
+            recipient.token_account,
+            recipient_account.key(),
+            VyperErrorCode::InvalidFeeRecipient
+        );
+        let recipient_token_account = Account::<TokenAccount>::try_from(recipient_account)?;
+        require_keys_eq!(
+            recipient_token_account.mint,
+            ctx.accounts.tranche_config.reserve_mint,
+            VyperErrorCode::InvalidFeeRecipient
+        );
+    }
+
+    msg!("create fee split");
+    let fee_split = &mut ctx.accounts.fee_split;
+    fee_split.tranche_config = ctx.accounts.tranche_config.key();
+    fee_split.recipients = input_data.recipients;
+
+    #[cfg(feature = "debug")]
+    msg!("+ recipients: {:?}", fee_split.recipients);
+
+    ctx.accounts.tranche_config.has_fee_split = true;
+
+    emit!(FeeSplitInitialized {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        recipients: ctx.accounts.fee_split.recipients.clone(),
+    });
+
+    Ok(())
+}
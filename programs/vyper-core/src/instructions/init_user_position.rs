@@ -0,0 +1,36 @@
+use crate::{
+    errors::VyperErrorCode,
+    state::{TrancheConfig, UserPosition},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitUserPositionContext<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK: Wallet owning the position
+    #[account()]
+    pub user: AccountInfo<'info>,
+
+    /// User position, one per wallet and tranche config
+    #[account(init, payer = payer, space = UserPosition::LEN, seeds = [tranche_config.key().as_ref(), b"user_position".as_ref(), user.key().as_ref()], bump)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitUserPositionContext>) -> Result<()> {
+    msg!("create user position for {}", ctx.accounts.user.key());
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.tranche_config = ctx.accounts.tranche_config.key();
+    user_position.user = ctx.accounts.user.key();
+    user_position.deposited_quantity = [0; 2];
+    user_position.deposit_cap_override = None;
+
+    Ok(())
+}
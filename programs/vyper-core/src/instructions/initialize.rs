@@ -1,6 +1,8 @@
 use crate::{
     errors::VyperErrorCode,
+    events::TrancheInitialized,
     state::{TrancheConfig, TrancheData},
+    utils::get_version_arr,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
@@ -78,7 +80,7 @@ pub fn handler(ctx: Context<InitializeContext>, input_data: InitializeInput) ->
 
     tranche_config.version = get_version_arr();
     tranche_config.owner = ctx.accounts.owner.key();
-    tranche_config.tranche_data = TrancheData::new(clock.slot);
+    tranche_config.tranche_data = TrancheData::new(clock.slot, clock.unix_timestamp);
     tranche_config
         .tranche_data
         .set_halt_flags(input_data.halt_flags)?;
@@ -100,20 +102,26 @@ pub fn handler(ctx: Context<InitializeContext>, input_data: InitializeInput) ->
     tranche_config.senior_tranche_mint = ctx.accounts.senior_tranche_mint.key();
     tranche_config.junior_tranche_mint = ctx.accounts.junior_tranche_mint.key();
     tranche_config.created_at = clock.unix_timestamp;
+    tranche_config.reserve_mint_decimals = ctx.accounts.reserve_mint.decimals;
+    tranche_config.tranche_mint_decimals = input_data.tranche_mint_decimals;
+    // reject a decimals difference that can't be represented
+    tranche_config.get_decimals_scale()?;
+
+    emit!(TrancheInitialized {
+        tranche_config: tranche_config.key(),
+        signer: ctx.accounts.payer.key(),
+        owner: tranche_config.owner,
+        reserve_mint: tranche_config.reserve_mint,
+        senior_tranche_mint: tranche_config.senior_tranche_mint,
+        junior_tranche_mint: tranche_config.junior_tranche_mint,
+        rate_program: tranche_config.rate_program,
+        rate_program_state: tranche_config.rate_program_state,
+        redeem_logic_program: tranche_config.redeem_logic_program,
+        redeem_logic_program_state: tranche_config.redeem_logic_program_state,
+        reserve_mint_decimals: tranche_config.reserve_mint_decimals,
+        tranche_mint_decimals: tranche_config.tranche_mint_decimals,
+        version: tranche_config.version,
+    });
 
     Ok(())
 }
-
-fn get_version_arr() -> [u8; 3] {
-    [
-        env!("CARGO_PKG_VERSION_MAJOR")
-            .parse::<u8>()
-            .expect("failed to parse major version"),
-        env!("CARGO_PKG_VERSION_MINOR")
-            .parse::<u8>()
-            .expect("failed to parse minor version"),
-        env!("CARGO_PKG_VERSION_PATCH")
-            .parse::<u8>()
-            .expect("failed to parse patch version"),
-    ]
-}
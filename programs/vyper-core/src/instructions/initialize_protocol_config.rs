@@ -0,0 +1,50 @@
+use crate::{program::VyperCore, state::ProtocolConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializeProtocolConfigContext<'info> {
+    /// Program upgrade authority, the only one allowed to create the protocol config
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    /// Protocol config account, a single PDA for the whole program
+    #[account(init, payer = upgrade_authority, space = ProtocolConfig::LEN, seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK: Admin of the protocol config
+    #[account()]
+    pub admin: AccountInfo<'info>,
+
+    /// CHECK: Owner of the token accounts receiving the protocol fees
+    #[account()]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, VyperCore>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()))]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug)]
+pub struct InitializeProtocolConfigInput {
+    pub protocol_fee_bps: u16,
+    pub halt_flags: u16,
+}
+
+pub fn handler(
+    ctx: Context<InitializeProtocolConfigContext>,
+    input_data: InitializeProtocolConfigInput,
+) -> Result<()> {
+    msg!("create protocol config");
+    let protocol_config = &mut ctx.accounts.protocol_config;
+
+    protocol_config.admin = ctx.accounts.admin.key();
+    protocol_config.treasury = ctx.accounts.treasury.key();
+    protocol_config.set_protocol_fee_bps(input_data.protocol_fee_bps)?;
+    protocol_config.set_halt_flags(input_data.halt_flags)?;
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::TrancheConfigMigrated,
+    state::{TrancheConfig, TrancheData},
+    utils::get_version_arr,
+};
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct MigrateContext<'info> {
+    /// Tranche config owner, pays for the additional rent if the account grows
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: deserialized in the handler, the stored layout can be older than the current one
+    #[account(mut, owner = crate::ID)]
+    pub tranche_config: AccountInfo<'info>,
+
+    /// Reserve mint of the tranche config, its decimals are stored from 0.4.0
+    pub reserve_mint: Box<Account<'info, Mint>>,
+
+    /// Senior tranche mint of the tranche config, its decimals are stored from 0.4.0
+    pub senior_tranche_mint: Box<Account<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateContext<'info> {
+    /// CpiContext for topping up the tranche config rent after a realloc
+    fn rent_transfer_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::Transfer<'info>> {
+        CpiContext::new(
+            self.system_program.to_account_info(),
+            system_program::Transfer {
+                from: self.owner.to_account_info(),
+                to: self.tranche_config.to_account_info(),
+            },
+        )
+    }
+}
+
+/// SlotTracking layout up to version 0.2.x: last update slot and padding, stale slot threshold
+const V2_SLOT_TRACKING_LEN: usize = 8 + 8 + 8;
+
+/// TrancheData layout up to version 0.1.x, before entry and exit fees
+#[derive(AnchorDeserialize)]
+struct TrancheDataV1 {
+    _deposited_quantity: [u64; 2],
+    _fee_to_collect_quantity: u64,
+    _reserve_fair_value: [[u8; 16]; 10],
+    _reserve_slot_tracking: [u8; V2_SLOT_TRACKING_LEN],
+    _tranche_fair_value: [[u8; 16]; 2],
+    _tranche_slot_tracking: [u8; V2_SLOT_TRACKING_LEN],
+    _halt_flags: u16,
+    _owner_restricted_ix: u16,
+    _deposit_cap: [Option<u64>; 2],
+}
+
+/// Offsets of the fixed size fields following the tranche data in the 0.1.x layout
+const V1_OWNER_OFFSET: usize = 4 * 32 + 1;
+const V1_VERSION_OFFSET: usize = V1_OWNER_OFFSET + 5 * 32;
+
+/// Offset of the tranche data in the tranche config, after the discriminator and two pubkeys
+const TRANCHE_DATA_OFFSET: usize = 8 + 2 * 32;
+
+/// Offsets of the end of the reserve and tranche fair value slot tracking in the 0.2.x tranche data
+const V2_RESERVE_SLOT_TRACKING_END: usize = 2 * 8 + 8 + 10 * 16 + V2_SLOT_TRACKING_LEN;
+const V2_TRANCHE_SLOT_TRACKING_END: usize =
+    V2_RESERVE_SLOT_TRACKING_END + 2 * 16 + V2_SLOT_TRACKING_LEN;
+
+/// stale_seconds_threshold: u64 and staleness_mode: StalenessMode appended to each slot tracking in 0.3.0
+const V3_SLOT_TRACKING_EXTENSION_LEN: usize = 8 + 1;
+
+/// entry_fee_bps and exit_fee_bps appended to the tranche data in 0.2.0
+const V2_FEES_LEN: usize = 2 * 2 + 2 * 2;
+
+/// End of the tranche data, deserialized with the given layout
+fn get_tranche_data_end<T: AnchorDeserialize>(data: &[u8]) -> Option<usize> {
+    let mut tranche_data_slice: &[u8] = data.get(TRANCHE_DATA_OFFSET..)?;
+    T::deserialize(&mut tranche_data_slice).ok()?;
+    Some(data.len() - tranche_data_slice.len())
+}
+
+/// Version stored after the tranche data ending at the given offset
+fn read_version(data: &[u8], tranche_data_end: usize) -> Option<[u8; 3]> {
+    let offset = tranche_data_end + V1_VERSION_OFFSET;
+    data.get(offset..offset + 3)?.try_into().ok()
+}
+
+/// Version stored in the tranche config.
+/// The tranche data size depends on the layout, so the version offset is computed from the tranche data end of each
+/// known layout and the stored version has to be one of the versions using that layout.
+/// Unknown versions, or a version matching more than one layout, are rejected
+fn get_stored_version(data: &[u8]) -> Result<[u8; 3]> {
+    let v1_tranche_data_end = get_tranche_data_end::<TrancheDataV1>(data);
+
+    // tranche data end of the layout, first and last versions using it
+    let layouts = [
+        (v1_tranche_data_end, [0, 1, 0], [0, 1, u8::MAX]),
+        (
+            v1_tranche_data_end.map(|end| end + V2_FEES_LEN),
+            [0, 2, 0],
+            [0, 2, u8::MAX],
+        ),
+        (
+            get_tranche_data_end::<TrancheData>(data),
+            [0, 3, 0],
+            get_version_arr(),
+        ),
+    ];
+
+    let mut stored_versions =
+        layouts
+            .into_iter()
+            .filter_map(|(tranche_data_end, first_version, last_version)| {
+                read_version(data, tranche_data_end?)
+                    .filter(|version| (first_version..=last_version).contains(version))
+            });
+
+    match (stored_versions.next(), stored_versions.next()) {
+        (Some(version), None) => Ok(version),
+        _ => err!(VyperErrorCode::UnsupportedTrancheConfigVersion),
+    }
+}
+
+pub fn handler(ctx: Context<MigrateContext>) -> Result<()> {
+    let tranche_config_info = ctx.accounts.tranche_config.to_account_info();
+
+    // check that the account is a tranche config
+    {
+        let data = tranche_config_info.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != TrancheConfig::discriminator() {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+    }
+
+    // grow the account if the current layout needs more space
+    let current_len = tranche_config_info.data_len();
+    if current_len < TrancheConfig::LEN {
+        msg!(
+            "realloc tranche config from {} to {}",
+            current_len,
+            TrancheConfig::LEN
+        );
+        let rent_exempt_lamports = Rent::get()?.minimum_balance(TrancheConfig::LEN);
+        let missing_lamports = rent_exempt_lamports.saturating_sub(tranche_config_info.lamports());
+        if missing_lamports > 0 {
+            system_program::transfer(ctx.accounts.rent_transfer_context(), missing_lamports)?;
+        }
+        tranche_config_info.realloc(TrancheConfig::LEN, true)?;
+    }
+
+    let stored_version = get_stored_version(&tranche_config_info.try_borrow_data()?)?;
+    msg!("stored version: {:?}", stored_version);
+
+    // 0.1.x -> 0.2.0: entry and exit fees appended to the tranche data
+    if stored_version < [0, 2, 0] {
+        msg!("migrate tranche config layout from 0.1.x");
+
+        let mut data = tranche_config_info.try_borrow_mut_data()?;
+        let tranche_data_end = get_tranche_data_end::<TrancheDataV1>(&data)
+            .ok_or(VyperErrorCode::UnsupportedTrancheConfigVersion)?;
+
+        require_keys_eq!(
+            Pubkey::try_from_slice(
+                &data[tranche_data_end + V1_OWNER_OFFSET..tranche_data_end + V1_OWNER_OFFSET + 32]
+            )?,
+            ctx.accounts.owner.key(),
+            VyperErrorCode::OwnerRestrictedIx
+        );
+
+        // entry_fee_bps and exit_fee_bps, zeroed
+        let data_len = data.len();
+        data.copy_within(
+            tranche_data_end..data_len - V2_FEES_LEN,
+            tranche_data_end + V2_FEES_LEN,
+        );
+        data[tranche_data_end..tranche_data_end + V2_FEES_LEN].fill(0);
+    }
+
+    // 0.2.x -> 0.3.0: stale seconds threshold and staleness mode appended to each slot tracking,
+    // the last update padding is read as a zero unix timestamp and the slot tracking stays slot based
+    if stored_version < [0, 3, 0] {
+        msg!("migrate tranche config layout from 0.2.x");
+
+        // the tail of the account is reserved space, the shifted out bytes are zeros
+        let mut data = tranche_config_info.try_borrow_mut_data()?;
+        let data_len = data.len();
+        for slot_tracking_end in [
+            V2_RESERVE_SLOT_TRACKING_END,
+            V2_TRANCHE_SLOT_TRACKING_END + V3_SLOT_TRACKING_EXTENSION_LEN,
+        ] {
+            let offset = TRANCHE_DATA_OFFSET + slot_tracking_end;
+            data.copy_within(
+                offset..data_len - V3_SLOT_TRACKING_EXTENSION_LEN,
+                offset + V3_SLOT_TRACKING_EXTENSION_LEN,
+            );
+            data[offset..offset + V3_SLOT_TRACKING_EXTENSION_LEN].fill(0);
+        }
+    }
+
+    // bump the version stored in the account
+    let mut tranche_config = Account::<TrancheConfig>::try_from(&tranche_config_info)?;
+    require_keys_eq!(
+        tranche_config.owner,
+        ctx.accounts.owner.key(),
+        VyperErrorCode::OwnerRestrictedIx
+    );
+
+    // 0.3.x -> 0.4.0: reserve and tranche mint decimals stored in the reserved space
+    if stored_version < [0, 4, 0] {
+        msg!("set tranche config mint decimals");
+        require_keys_eq!(
+            tranche_config.reserve_mint,
+            ctx.accounts.reserve_mint.key(),
+            anchor_lang::error::ErrorCode::ConstraintHasOne
+        );
+        require_keys_eq!(
+            tranche_config.senior_tranche_mint,
+            ctx.accounts.senior_tranche_mint.key(),
+            anchor_lang::error::ErrorCode::ConstraintHasOne
+        );
+        tranche_config.reserve_mint_decimals = ctx.accounts.reserve_mint.decimals;
+        tranche_config.tranche_mint_decimals = ctx.accounts.senior_tranche_mint.decimals;
+        tranche_config.get_decimals_scale()?;
+    }
+
+    #[cfg(feature = "debug")]
+    msg!("+ old value: {:?}", tranche_config.version);
+
+    let old_version = tranche_config.version;
+    tranche_config.version = get_version_arr();
+
+    #[cfg(feature = "debug")]
+    msg!("+ new value: {:?}", tranche_config.version);
+
+    emit!(TrancheConfigMigrated {
+        tranche_config: tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        old_version,
+        new_version: tranche_config.version,
+    });
+
+    tranche_config.exit(ctx.program_id)
+}
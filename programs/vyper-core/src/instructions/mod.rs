@@ -1,13 +1,69 @@
+pub mod accept_owner;
+pub mod add_allowlist_entry;
+pub mod apply_plugin_update;
+pub mod apply_tranche_data_update;
+pub mod cancel_epoch_roll;
+pub mod cancel_plugin_update;
+pub mod cancel_tranche_data_update;
+pub mod claim_queued_request;
+pub mod close_tranche;
 pub mod collect_fee;
+pub mod collect_fee_split;
+pub mod collect_protocol_fee;
+pub mod create_queued_request;
 pub mod deposit;
+pub mod guardian_halt;
+pub mod init_dead_shares;
+pub mod init_fee_split;
+pub mod init_user_position;
 pub mod initialize;
+pub mod initialize_protocol_config;
+pub mod migrate;
+pub mod process_queued_request;
+pub mod propose_owner;
+pub mod queue_epoch_roll;
+pub mod queue_plugin_update;
+pub mod queue_tranche_data_update;
 pub mod redeem;
 pub mod refresh_tranche_fair_value;
+pub mod remove_allowlist_entry;
+pub mod roll_epoch;
+pub mod set_user_deposit_cap;
+pub mod settle;
+pub mod update_protocol_config;
 pub mod update_tranche_data;
 
+pub use accept_owner::*;
+pub use add_allowlist_entry::*;
+pub use apply_plugin_update::*;
+pub use apply_tranche_data_update::*;
+pub use cancel_epoch_roll::*;
+pub use cancel_plugin_update::*;
+pub use cancel_tranche_data_update::*;
+pub use claim_queued_request::*;
+pub use close_tranche::*;
 pub use collect_fee::*;
+pub use collect_fee_split::*;
+pub use collect_protocol_fee::*;
+pub use create_queued_request::*;
 pub use deposit::*;
+pub use guardian_halt::*;
+pub use init_dead_shares::*;
+pub use init_fee_split::*;
+pub use init_user_position::*;
 pub use initialize::*;
+pub use initialize_protocol_config::*;
+pub use migrate::*;
+pub use process_queued_request::*;
+pub use propose_owner::*;
+pub use queue_epoch_roll::*;
+pub use queue_plugin_update::*;
+pub use queue_tranche_data_update::*;
 pub use redeem::*;
 pub use refresh_tranche_fair_value::*;
+pub use remove_allowlist_entry::*;
+pub use roll_epoch::*;
+pub use set_user_deposit_cap::*;
+pub use settle::*;
+pub use update_protocol_config::*;
 pub use update_tranche_data::*;
@@ -0,0 +1,502 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::{DeadSharesLocked, QueuedRequestProcessed, QueuedRequestRejected},
+    instructions::mint_dead_shares,
+    state::{
+        ProtocolConfig, QueuedRequest, QueuedRequestKind, TrancheConfig, TrancheHaltFlags,
+        UserPosition,
+    },
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
+use boolinator::Boolinator;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+#[derive(Accounts)]
+pub struct ProcessQueuedRequestContext<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        has_one = junior_tranche_mint,
+        has_one = senior_tranche_mint,
+        has_one = tranche_authority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Protocol config account, checked for protocol level halts
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// CHECK:
+    #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
+    pub tranche_authority: AccountInfo<'info>,
+
+    /// Queued request to execute
+    #[account(mut,
+        has_one = tranche_config,
+        constraint = !queued_request.processed @ VyperErrorCode::QueuedRequestAlreadyProcessed)]
+    pub queued_request: Box<Account<'info, QueuedRequest>>,
+
+    /// Senior tranche mint
+    #[account(mut)]
+    pub senior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// Junior tranche mint
+    #[account(mut)]
+    pub junior_tranche_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [queued_request.key().as_ref(), b"senior_escrow".as_ref()], bump)]
+    pub senior_tranche_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [queued_request.key().as_ref(), b"junior_escrow".as_ref()], bump)]
+    pub junior_tranche_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Quantities computed while checking a queued request, applied once all the checks passed
+#[derive(Default)]
+struct RequestQuantities {
+    fee_quantity: [u64; 2],
+    tranche_quantity: [u64; 2],
+    reserve_quantity: [u64; 2],
+    redeemed_reserve_quantity: [u64; 2],
+    dead_shares_count: [u64; 2],
+    deposited_quantity: [u64; 2],
+}
+
+impl<'info> ProcessQueuedRequestContext<'info> {
+    fn are_valid(&self) -> Result<()> {
+        // check that the tranche fair value has been refreshed after the request, settled fair values are final
+        let tranche_data = &self.tranche_config.tranche_data;
+        if !self.tranche_config.settled {
+            let slot_tracking = &tranche_data.tranche_fair_value.slot_tracking;
+            (slot_tracking.get_last_update_slot() > self.queued_request.requested_slot)
+                .ok_or(VyperErrorCode::QueuedRequestNotRefreshed)?;
+            let clock = Clock::get()?;
+            (!slot_tracking.is_stale(clock.slot, clock.unix_timestamp)?)
+                .ok_or(VyperErrorCode::StaleFairValue)?;
+        }
+
+        Result::Ok(())
+    }
+
+    /// Check that the requested action is not halted at tranche or protocol level
+    fn check_halts(&self) -> Result<()> {
+        let halt_flag = match self.queued_request.kind {
+            QueuedRequestKind::Deposit => TrancheHaltFlags::HALT_DEPOSITS,
+            QueuedRequestKind::Redeem => TrancheHaltFlags::HALT_REDEEMS,
+        };
+
+        // check that the requested action is not halted
+        let tranche_data = &self.tranche_config.tranche_data;
+        (!tranche_data.get_halt_flags()?.contains(halt_flag)).ok_or(VyperErrorCode::HaltError)?;
+
+        // check that the requested action is not halted at protocol level
+        (!self.protocol_config.get_halt_flags()?.contains(halt_flag))
+            .ok_or(VyperErrorCode::ProtocolHaltError)?;
+
+        Result::Ok(())
+    }
+
+    /// Compute the tranches minted by a queued deposit and check them against the tranche constraints
+    fn check_deposit(
+        &self,
+        quantities: &mut RequestQuantities,
+        user_position: Option<&UserPosition>,
+        tranche_supply: [u64; 2],
+    ) -> Result<()> {
+        self.check_halts()?;
+
+        // compute entry fees
+        let tranche_config = &self.tranche_config;
+        for (i, &reserve_qty) in self.queued_request.quantity.iter().enumerate() {
+            quantities.fee_quantity[i] =
+                tranche_config.tranche_data.get_entry_fee(i, reserve_qty)?;
+            quantities.reserve_quantity[i] = reserve_qty
+                .checked_sub(quantities.fee_quantity[i])
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+
+        // compute the tranches to mint
+        let decimals_scale = tranche_config.get_decimals_scale()?;
+        for (i, &net_reserve_qty) in quantities.reserve_quantity.iter().enumerate() {
+            let tranche_fv =
+                Decimal::deserialize(tranche_config.tranche_data.tranche_fair_value.value[i]);
+            quantities.tranche_quantity[i] = Decimal::from(net_reserve_qty)
+                .checked_div(
+                    tranche_fv
+                        .checked_mul(decimals_scale)
+                        .ok_or(VyperErrorCode::MathError)?,
+                )
+                .ok_or(VyperErrorCode::MathError)?
+                .floor()
+                .to_u64()
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+
+        // lock the dead shares of the first deposit of a side, out of the requested tranches
+        for (i, (tranche_qty, dsc)) in quantities
+            .tranche_quantity
+            .iter_mut()
+            .zip(quantities.dead_shares_count.iter_mut())
+            .enumerate()
+        {
+            *dsc = tranche_config.get_initial_dead_shares(
+                i,
+                quantities.reserve_quantity[i],
+                tranche_supply[i],
+            )?;
+            *tranche_qty = tranche_qty
+                .checked_sub(*dsc)
+                .ok_or(VyperErrorCode::MinInitialDepositNotReached)?;
+        }
+
+        // check slippage
+        let min_tranche_out = self.queued_request.min_tranche_out;
+        if quantities
+            .tranche_quantity
+            .iter()
+            .zip(min_tranche_out.iter())
+            .any(|(&tranche_qty, min_out)| tranche_qty < min_out.unwrap_or(0))
+        {
+            msg!(
+                "minted tranches {:?} below the minimum {:?}",
+                quantities.tranche_quantity,
+                min_tranche_out
+            );
+            return err!(VyperErrorCode::SlippageExceeded);
+        }
+
+        // check if deposits exceeded the wallet cap, the position is updated once all the checks passed
+        if tranche_config.has_wallet_deposit_cap() {
+            msg!("check wallet deposit cap");
+            let mut user_position = user_position
+                .ok_or(VyperErrorCode::MissingUserPosition)?
+                .clone();
+            let wallet_deposit_cap = tranche_config.get_wallet_deposit_cap(&user_position);
+            user_position.add_deposit(quantities.reserve_quantity, wallet_deposit_cap)?;
+        }
+
+        // check if deposits exceeded cap
+        msg!("check deposit cap");
+        tranche_config.check_deposit_cap(quantities.reserve_quantity)?;
+
+        // check that the deposit keeps the senior junior ratio within the bounds
+        for (i, &net_reserve_qty) in quantities.reserve_quantity.iter().enumerate() {
+            quantities.deposited_quantity[i] = tranche_config.tranche_data.deposited_quantity[i]
+                .checked_add(net_reserve_qty)
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+        msg!("check coverage ratio");
+        tranche_config.check_coverage_ratio(
+            tranche_config.tranche_data.deposited_quantity,
+            quantities.deposited_quantity,
+        )
+    }
+
+    /// Compute the reserve redeemed by a queued redeem and check it against the tranche constraints
+    fn check_redeem(
+        &self,
+        quantities: &mut RequestQuantities,
+        tranche_supply: [u64; 2],
+    ) -> Result<()> {
+        self.check_halts()?;
+
+        // compute the redeemed reserve and the exit fees
+        let tranche_config = &self.tranche_config;
+        let decimals_scale = tranche_config.get_decimals_scale()?;
+        quantities.tranche_quantity = self.queued_request.quantity;
+        for (i, &tranche_qty) in quantities.tranche_quantity.iter().enumerate() {
+            let tranche_fv =
+                Decimal::deserialize(tranche_config.tranche_data.tranche_fair_value.value[i]);
+            quantities.redeemed_reserve_quantity[i] = Decimal::from(tranche_qty)
+                .checked_mul(tranche_fv)
+                .and_then(|qty| qty.checked_mul(decimals_scale))
+                .ok_or(VyperErrorCode::MathError)?
+                .floor()
+                .to_u64()
+                .ok_or(VyperErrorCode::MathError)?;
+            quantities.fee_quantity[i] = tranche_config
+                .tranche_data
+                .get_exit_fee(i, quantities.redeemed_reserve_quantity[i])?;
+            quantities.reserve_quantity[i] = quantities.redeemed_reserve_quantity[i]
+                .checked_sub(quantities.fee_quantity[i])
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+
+        // check slippage
+        let reserve_to_claim_quantity = quantities.reserve_quantity.iter().sum::<u64>();
+        if let Some(min_reserve_out) = self.queued_request.min_reserve_out {
+            if reserve_to_claim_quantity < min_reserve_out {
+                msg!(
+                    "redeemed reserve {} below the minimum {}",
+                    reserve_to_claim_quantity,
+                    min_reserve_out
+                );
+                return err!(VyperErrorCode::SlippageExceeded);
+            }
+        }
+
+        // check that the redeem keeps the senior junior ratio within the bounds
+        for (i, &redeemed_reserve_qty) in quantities.redeemed_reserve_quantity.iter().enumerate() {
+            quantities.deposited_quantity[i] = tranche_config.tranche_data.deposited_quantity[i]
+                .checked_sub(redeemed_reserve_qty)
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+        // the last holder of a side can always exit
+        if tranche_config.is_last_holder_exit(tranche_supply, quantities.tranche_quantity) {
+            return Ok(());
+        }
+        msg!("check coverage ratio");
+        tranche_config.check_coverage_ratio(
+            tranche_config.tranche_data.deposited_quantity,
+            quantities.deposited_quantity,
+        )
+    }
+
+    /// Mark the request as processed without executing it, the locked tokens are returned on claim
+    fn reject(&mut self, tranche_quantity: [u64; 2], reserve_quantity: [u64; 2]) -> Result<()> {
+        let queued_request = &mut self.queued_request;
+        if queued_request.kind == QueuedRequestKind::Deposit {
+            // the locked reserve stays in the queued reserve until claimed
+            queued_request.reserve_to_claim_quantity = queued_request.quantity.iter().sum::<u64>();
+        }
+        queued_request.processed = true;
+
+        emit!(QueuedRequestRejected {
+            tranche_config: self.tranche_config.key(),
+            signer: self.signer.key(),
+            user: queued_request.user,
+            kind: queued_request.kind,
+            tranche_quantity,
+            reserve_quantity,
+        });
+
+        Result::Ok(())
+    }
+
+    /// CpiContext for minting tranches to the request escrow
+    fn mint_to_context(
+        &self,
+        mint: &Account<'info, Mint>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            MintTo {
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: self.tranche_authority.clone(),
+            },
+        )
+    }
+
+    /// CpiContext for burning tranches locked in the request escrow
+    fn burn_context(
+        &self,
+        mint: &Account<'info, Mint>,
+        from: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Burn {
+                mint: mint.to_account_info(),
+                from: from.to_account_info(),
+                authority: self.tranche_authority.clone(),
+            },
+        )
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProcessQueuedRequestContext<'info>>,
+) -> Result<()> {
+    // check if accounts are valid
+    msg!("check if accounts are valid");
+    ctx.accounts.are_valid()?;
+
+    let kind = ctx.accounts.queued_request.kind;
+    let quantity = ctx.accounts.queued_request.quantity;
+    let user = ctx.accounts.queued_request.user;
+    let tranche_supply = [
+        ctx.accounts.senior_tranche_mint.supply,
+        ctx.accounts.junior_tranche_mint.supply,
+    ];
+
+    // the position of the requester is passed as remaining account, it is required when deposits are tracked per wallet.
+    // A position not created by the user rejects the deposit, a missing account fails the transaction
+    let user_position = UserPosition::load(
+        ctx.remaining_accounts,
+        ctx.accounts.tranche_config.key(),
+        user,
+    );
+    let mut user_position = if ctx.accounts.tranche_config.has_wallet_deposit_cap() {
+        user_position?
+    } else {
+        user_position.unwrap_or_default()
+    };
+
+    // a request failing a check is rejected instead of being left unprocessed, the locked tokens are returned on claim
+    let mut quantities = RequestQuantities::default();
+    let checked = match kind {
+        QueuedRequestKind::Deposit => {
+            ctx.accounts
+                .check_deposit(&mut quantities, user_position.as_deref(), tranche_supply)
+        }
+        QueuedRequestKind::Redeem => ctx.accounts.check_redeem(&mut quantities, tranche_supply),
+    };
+    if let Err(err) = checked {
+        msg!("reject request: {}", err);
+        return ctx
+            .accounts
+            .reject(quantities.tranche_quantity, quantities.reserve_quantity);
+    }
+
+    let RequestQuantities {
+        fee_quantity,
+        tranche_quantity,
+        reserve_quantity,
+        redeemed_reserve_quantity,
+        dead_shares_count,
+        deposited_quantity,
+    } = quantities;
+
+    match kind {
+        QueuedRequestKind::Deposit => {
+            msg!("entry fee: {:?}", fee_quantity);
+            ctx.accounts.tranche_config.add_fee(
+                fee_quantity.iter().sum::<u64>(),
+                &ctx.accounts.protocol_config,
+            )?;
+
+            // track the deposit in the user position, the wallet deposit cap has already been checked
+            if ctx.accounts.tranche_config.has_wallet_deposit_cap() {
+                if let Some(user_position) = user_position.as_mut() {
+                    msg!("increase user position deposited_quantity");
+                    let wallet_deposit_cap = ctx
+                        .accounts
+                        .tranche_config
+                        .get_wallet_deposit_cap(user_position);
+                    user_position.add_deposit(reserve_quantity, wallet_deposit_cap)?;
+                    user_position.exit(ctx.program_id)?;
+                }
+            }
+
+            // increase deposited_quantity
+            ctx.accounts.tranche_config.tranche_data.deposited_quantity = deposited_quantity;
+
+            // the locked reserve is now tracked by the tranche data
+            ctx.accounts.tranche_config.queued_reserve_quantity = ctx
+                .accounts
+                .tranche_config
+                .queued_reserve_quantity
+                .checked_sub(quantity.iter().sum::<u64>())
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+        QueuedRequestKind::Redeem => {
+            // decrease deposited_quantity
+            ctx.accounts.tranche_config.tranche_data.deposited_quantity = deposited_quantity;
+            msg!("exit fee: {:?}", fee_quantity);
+            ctx.accounts.tranche_config.add_fee(
+                fee_quantity.iter().sum::<u64>(),
+                &ctx.accounts.protocol_config,
+            )?;
+
+            // release the wallet deposit cap
+            if let Some(user_position) = user_position.as_mut() {
+                msg!("decrease user position deposited_quantity");
+                user_position.remove_deposit(redeemed_reserve_quantity);
+                user_position.exit(ctx.program_id)?;
+            }
+
+            // the redeemed reserve is owed to the user until claimed
+            let reserve_to_claim_quantity = reserve_quantity.iter().sum::<u64>();
+            ctx.accounts.tranche_config.queued_reserve_quantity = ctx
+                .accounts
+                .tranche_config
+                .queued_reserve_quantity
+                .checked_add(reserve_to_claim_quantity)
+                .ok_or(VyperErrorCode::MathError)?;
+            ctx.accounts.queued_request.reserve_to_claim_quantity = reserve_to_claim_quantity;
+        }
+    }
+
+    // mint the dead shares to the tranche authority
+    for (&dsc, mint) in dead_shares_count.iter().zip([
+        &ctx.accounts.senior_tranche_mint,
+        &ctx.accounts.junior_tranche_mint,
+    ]) {
+        if dsc > 0 {
+            mint_dead_shares(
+                &ctx.accounts.tranche_config,
+                mint,
+                &ctx.accounts.tranche_authority,
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                dsc,
+            )?;
+        }
+    }
+    if dead_shares_count.iter().any(|&dsc| dsc > 0) {
+        emit!(DeadSharesLocked {
+            tranche_config: ctx.accounts.tranche_config.key(),
+            dead_shares_quantity: dead_shares_count,
+        });
+    }
+
+    // mint or burn tranches in the request escrow
+    let authority_seeds = ctx.accounts.tranche_config.authority_seeds();
+    let escrows = [
+        (
+            &ctx.accounts.senior_tranche_mint,
+            &ctx.accounts.senior_tranche_escrow,
+        ),
+        (
+            &ctx.accounts.junior_tranche_mint,
+            &ctx.accounts.junior_tranche_escrow,
+        ),
+    ];
+    for (&tranche_qty, (mint, escrow)) in tranche_quantity.iter().zip(escrows) {
+        if tranche_qty == 0 {
+            continue;
+        }
+        match kind {
+            QueuedRequestKind::Deposit => {
+                msg!("mint {} tranches to {}", tranche_qty, mint.key());
+                token::mint_to(
+                    ctx.accounts
+                        .mint_to_context(mint, escrow)
+                        .with_signer(&[&authority_seeds]),
+                    tranche_qty,
+                )?;
+            }
+            QueuedRequestKind::Redeem => {
+                msg!("burn {} tranches of {}", tranche_qty, mint.key());
+                token::burn(
+                    ctx.accounts
+                        .burn_context(mint, escrow)
+                        .with_signer(&[&authority_seeds]),
+                    tranche_qty,
+                )?;
+            }
+        }
+    }
+
+    ctx.accounts.queued_request.processed = true;
+
+    let tranche_data = &ctx.accounts.tranche_config.tranche_data;
+    emit!(QueuedRequestProcessed {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.signer.key(),
+        user,
+        kind,
+        tranche_quantity,
+        reserve_quantity,
+        fee_quantity,
+        tranche_fair_value: tranche_data.tranche_fair_value.value,
+        deposited_quantity: tranche_data.deposited_quantity,
+    });
+
+    Ok(())
+}
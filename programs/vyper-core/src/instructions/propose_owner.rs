@@ -0,0 +1,37 @@
+use crate::{errors::VyperErrorCode, events::OwnerProposed, state::TrancheConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeOwnerContext<'info> {
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut, has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// CHECK: Proposed owner of the tranche config, it needs to accept the ownership
+    #[account()]
+    pub new_owner: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<ProposeOwnerContext>) -> Result<()> {
+    let tranche_config = &mut ctx.accounts.tranche_config;
+
+    msg!("propose new tranche_config owner");
+
+    #[cfg(feature = "debug")]
+    msg!("+ old value: {:?}", tranche_config.pending_owner);
+
+    tranche_config.pending_owner = Some(ctx.accounts.new_owner.key());
+
+    #[cfg(feature = "debug")]
+    msg!("+ new value: {:?}", tranche_config.pending_owner);
+
+    emit!(OwnerProposed {
+        tranche_config: tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        pending_owner: ctx.accounts.new_owner.key(),
+    });
+
+    Ok(())
+}
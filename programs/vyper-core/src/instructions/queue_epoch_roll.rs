@@ -0,0 +1,82 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::EpochRollQueued,
+    state::{PendingEpochRoll, TrancheConfig, TrancheLifecycle},
+    utils::Input,
+};
+use anchor_lang::prelude::*;
+use boolinator::Boolinator;
+
+#[derive(Accounts)]
+pub struct QueueEpochRollContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(
+        has_one = owner,
+        has_one = redeem_logic_program,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued epoch roll, one per tranche config
+    #[account(init, payer = owner, space = PendingEpochRoll::LEN, seeds = [tranche_config.key().as_ref(), b"pending_epoch_roll".as_ref()], bump)]
+    pub pending_epoch_roll: Box<Account<'info, PendingEpochRoll>>,
+
+    /// CHECK: redeem logic program of the tranche config
+    #[account()]
+    pub redeem_logic_program: AccountInfo<'info>,
+
+    /// CHECK: redeem logic state of the next epoch, owned by the redeem logic program
+    #[account(owner = redeem_logic_program.key())]
+    pub next_redeem_logic_program_state: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
+pub struct QueueEpochRollInput {
+    /// subscription window and maturity of the next epoch
+    pub next_lifecycle: TrancheLifecycle,
+}
+
+impl Input for QueueEpochRollInput {
+    fn is_valid(&self) -> Result<()> {
+        self.next_lifecycle
+            .is_valid()
+            .ok_or(VyperErrorCode::InvalidLifecycle)?;
+
+        Result::Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<QueueEpochRollContext>, input_data: QueueEpochRollInput) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // check input
+    msg!("check if input is valid");
+    input_data.is_valid()?;
+
+    let executable_slot = clock
+        .slot
+        .checked_add(ctx.accounts.tranche_config.timelock_slots)
+        .ok_or(VyperErrorCode::MathError)?;
+
+    msg!("queue epoch roll, executable from slot {}", executable_slot);
+    let pending_epoch_roll = &mut ctx.accounts.pending_epoch_roll;
+    pending_epoch_roll.tranche_config = ctx.accounts.tranche_config.key();
+    pending_epoch_roll.redeem_logic_program_state =
+        ctx.accounts.next_redeem_logic_program_state.key();
+    pending_epoch_roll.lifecycle = input_data.next_lifecycle;
+    pending_epoch_roll.queued_slot = clock.slot;
+    pending_epoch_roll.executable_slot = executable_slot;
+
+    emit!(EpochRollQueued {
+        tranche_config: pending_epoch_roll.tranche_config,
+        redeem_logic_program_state: pending_epoch_roll.redeem_logic_program_state,
+        lifecycle: pending_epoch_roll.lifecycle,
+        executable_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::PluginUpdateQueued,
+    state::{PendingPluginUpdate, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QueuePluginUpdateContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued plugin update, one per tranche config
+    #[account(init, payer = owner, space = PendingPluginUpdate::LEN, seeds = [tranche_config.key().as_ref(), b"pending_plugin_update".as_ref()], bump)]
+    pub pending_plugin_update: Box<Account<'info, PendingPluginUpdate>>,
+
+    /// CHECK:
+    #[account()]
+    pub rate_program: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account()]
+    pub rate_program_state: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account()]
+    pub redeem_logic_program: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account()]
+    pub redeem_logic_program_state: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<QueuePluginUpdateContext>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    let executable_slot = clock
+        .slot
+        .checked_add(ctx.accounts.tranche_config.timelock_slots)
+        .ok_or(VyperErrorCode::MathError)?;
+
+    msg!(
+        "queue plugin update, executable from slot {}",
+        executable_slot
+    );
+    let pending_plugin_update = &mut ctx.accounts.pending_plugin_update;
+    pending_plugin_update.tranche_config = ctx.accounts.tranche_config.key();
+    pending_plugin_update.rate_program = ctx.accounts.rate_program.key();
+    pending_plugin_update.rate_program_state = ctx.accounts.rate_program_state.key();
+    pending_plugin_update.redeem_logic_program = ctx.accounts.redeem_logic_program.key();
+    pending_plugin_update.redeem_logic_program_state =
+        ctx.accounts.redeem_logic_program_state.key();
+    pending_plugin_update.queued_slot = clock.slot;
+    pending_plugin_update.executable_slot = executable_slot;
+
+    emit!(PluginUpdateQueued {
+        tranche_config: pending_plugin_update.tranche_config,
+        rate_program: pending_plugin_update.rate_program,
+        rate_program_state: pending_plugin_update.rate_program_state,
+        redeem_logic_program: pending_plugin_update.redeem_logic_program,
+        redeem_logic_program_state: pending_plugin_update.redeem_logic_program_state,
+        executable_slot,
+    });
+
+    Ok(())
+}
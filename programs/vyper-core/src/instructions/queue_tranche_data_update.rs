@@ -0,0 +1,53 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::TrancheDataUpdateQueued,
+    instructions::UpdateTrancheDataInput,
+    state::{PendingTrancheDataUpdate, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QueueTrancheDataUpdateContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Queued tranche data update, one per tranche config
+    #[account(init, payer = owner, space = PendingTrancheDataUpdate::LEN, seeds = [tranche_config.key().as_ref(), b"pending_tranche_data_update".as_ref()], bump)]
+    pub pending_tranche_data_update: Box<Account<'info, PendingTrancheDataUpdate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<QueueTrancheDataUpdateContext>,
+    input_data: UpdateTrancheDataInput,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    let executable_slot = clock
+        .slot
+        .checked_add(ctx.accounts.tranche_config.timelock_slots)
+        .ok_or(VyperErrorCode::MathError)?;
+
+    msg!(
+        "queue tranche data update, executable from slot {}",
+        executable_slot
+    );
+    let pending_tranche_data_update = &mut ctx.accounts.pending_tranche_data_update;
+    pending_tranche_data_update.tranche_config = ctx.accounts.tranche_config.key();
+    pending_tranche_data_update.update = input_data.clone();
+    pending_tranche_data_update.queued_slot = clock.slot;
+    pending_tranche_data_update.executable_slot = executable_slot;
+
+    emit!(TrancheDataUpdateQueued {
+        tranche_config: pending_tranche_data_update.tranche_config,
+        update: input_data,
+        executable_slot,
+    });
+
+    Ok(())
+}
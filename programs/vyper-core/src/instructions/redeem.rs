@@ -1,6 +1,11 @@
 use crate::{
     errors::VyperErrorCode,
-    state::{OwnerRestrictedIxFlags, TrancheConfig, TrancheHaltFlags},
+    events::Redeemed,
+    instructions::refresh_if_stale,
+    state::{
+        AllowlistEntry, AllowlistRestrictedIxFlags, OwnerRestrictedIxFlags, ProtocolConfig,
+        TrancheConfig, TrancheHaltFlags, UserPosition,
+    },
     utils::Input,
 };
 use anchor_lang::prelude::*;
@@ -15,10 +20,17 @@ pub struct RedeemContext<'info> {
 
     #[account(mut, 
         // constraint = !vault.value.last_update.is_stale(clock.slot)? @ ErrorCode::VaultIsNotRefreshed,
+        has_one = junior_tranche_mint,
+        has_one = senior_tranche_mint,
         has_one = reserve,
-        has_one = tranche_authority)]
+        has_one = tranche_authority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
     pub tranche_config: Box<Account<'info, TrancheConfig>>,
 
+    /// Protocol config account, checked for protocol level halts
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
     /// CHECK:
     #[account(seeds = [tranche_config.key().as_ref(), b"authority".as_ref()], bump)]
     pub tranche_authority: AccountInfo<'info>,
@@ -52,7 +64,6 @@ pub struct RedeemContext<'info> {
 
 impl<'info> RedeemContext<'info> {
     fn are_valid(&self) -> Result<()> {
-        let clock = Clock::get()?;
         let tranche_data = &self.tranche_config.tranche_data;
 
         // check that deposits are not halted
@@ -61,12 +72,17 @@ impl<'info> RedeemContext<'info> {
             .contains(TrancheHaltFlags::HALT_REDEEMS))
         .ok_or(VyperErrorCode::HaltError)?;
 
-        // check that tranche fair values are not halted
-        (!tranche_data
-            .tranche_fair_value
-            .slot_tracking
-            .is_stale(clock.slot)?)
-        .ok_or(VyperErrorCode::StaleFairValue)?;
+        // check that redeems are not halted at protocol level
+        (!self
+            .protocol_config
+            .get_halt_flags()?
+            .contains(TrancheHaltFlags::HALT_REDEEMS))
+        .ok_or(VyperErrorCode::ProtocolHaltError)?;
+
+        // check that redeems are allowed in the current lifecycle phase
+        self.tranche_config
+            .lifecycle
+            .check_redeem_allowed(Clock::get()?.unix_timestamp)?;
 
         // check if the current ix is restricted to owner
         if tranche_data
@@ -123,6 +139,9 @@ impl<'info> RedeemContext<'info> {
 #[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
 pub struct RedeemInput {
     pub tranche_quantity: [u64; 2],
+
+    /// minimum reserve quantity to receive, net of exit fees, None disables the check
+    pub min_reserve_out: Option<u64>,
 }
 
 impl Input for RedeemInput {
@@ -136,7 +155,10 @@ impl Input for RedeemInput {
     }
 }
 
-pub fn handler(ctx: Context<RedeemContext>, input_data: RedeemInput) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, RedeemContext<'info>>,
+    input_data: RedeemInput,
+) -> Result<()> {
     // check if accounts are valid
     msg!("check if accounts are valid");
     ctx.accounts.are_valid()?;
@@ -145,14 +167,49 @@ pub fn handler(ctx: Context<RedeemContext>, input_data: RedeemInput) -> Result<(
     msg!("check if input is valid");
     input_data.is_valid()?;
 
+    // check if the current ix is restricted to allowlisted users
+    if ctx
+        .accounts
+        .tranche_config
+        .get_allowlist_restricted_ixs()?
+        .contains(AllowlistRestrictedIxFlags::REDEEMS)
+    {
+        AllowlistEntry::is_allowlisted(
+            ctx.remaining_accounts,
+            ctx.accounts.tranche_config.key(),
+            ctx.accounts.signer.key(),
+        )
+        .ok_or(VyperErrorCode::NotAllowlisted)?;
+    }
+
+    // refresh the tranche fair value if stale, the plugin accounts are passed as remaining accounts
+    let tranche_supply = [
+        ctx.accounts.senior_tranche_mint.supply,
+        ctx.accounts.junior_tranche_mint.supply,
+    ];
+    refresh_if_stale(
+        &mut ctx.accounts.tranche_config,
+        &ctx.accounts.protocol_config,
+        tranche_supply,
+        ctx.accounts.signer.key(),
+        ctx.remaining_accounts,
+    )?;
+
     // decrease deposited_quantity
     msg!("decrease deposited_quantity");
+    let decimals_scale = ctx.accounts.tranche_config.get_decimals_scale()?;
     let tranche_data = &mut ctx.accounts.tranche_config.tranche_data;
+    let old_deposited_quantity = tranche_data.deposited_quantity;
     let mut total_reserve_to_redeem = 0u64;
-    for i in 0..input_data.tranche_quantity.len() {
+    let mut exit_fee_quantity: [u64; 2] = [0; 2];
+    let mut redeemed_reserve_quantity: [u64; 2] = [0; 2];
+    for (i, exit_fee_qty) in exit_fee_quantity.iter_mut().enumerate() {
         let cur_tranche_fv = Decimal::deserialize(tranche_data.tranche_fair_value.value[i]);
         let redeemed_tranche_qty = Decimal::from(input_data.tranche_quantity[i]);
-        let redeemed_reserve_qty = redeemed_tranche_qty * cur_tranche_fv;
+        let redeemed_reserve_qty = redeemed_tranche_qty
+            .checked_mul(cur_tranche_fv)
+            .and_then(|qty| qty.checked_mul(decimals_scale))
+            .ok_or(VyperErrorCode::MathError)?;
 
         #[cfg(feature = "debug")]
         {
@@ -164,14 +221,69 @@ pub fn handler(ctx: Context<RedeemContext>, input_data: RedeemInput) -> Result<(
 
         let redeemed_reserve_qty_u64 = redeemed_reserve_qty.floor().to_u64().ok_or(VyperErrorCode::MathError)?;
 
+        redeemed_reserve_quantity[i] = redeemed_reserve_qty_u64;
+        *exit_fee_qty = tranche_data.get_exit_fee(i, redeemed_reserve_qty_u64)?;
+
         total_reserve_to_redeem = total_reserve_to_redeem
-            .checked_add(redeemed_reserve_qty_u64)
+            .checked_add(
+                redeemed_reserve_qty_u64
+                    .checked_sub(*exit_fee_qty)
+                    .ok_or(VyperErrorCode::MathError)?,
+            )
             .ok_or(VyperErrorCode::MathError)?;
         tranche_data.deposited_quantity[i] = tranche_data.deposited_quantity[i]
             .checked_sub(redeemed_reserve_qty_u64)
             .ok_or(VyperErrorCode::MathError)?;
     }
 
+    // check that the redeem keeps the senior junior ratio within the bounds, the last holder of a side can always exit
+    if !ctx
+        .accounts
+        .tranche_config
+        .is_last_holder_exit(tranche_supply, input_data.tranche_quantity)
+    {
+        msg!("check coverage ratio");
+        ctx.accounts.tranche_config.check_coverage_ratio(
+            old_deposited_quantity,
+            ctx.accounts.tranche_config.tranche_data.deposited_quantity,
+        )?;
+    }
+
+    // release the wallet deposit cap, the user position is required when deposits are tracked per wallet
+    let user_position = UserPosition::load(
+        ctx.remaining_accounts,
+        ctx.accounts.tranche_config.key(),
+        ctx.accounts.signer.key(),
+    );
+    let user_position = if ctx.accounts.tranche_config.has_wallet_deposit_cap() {
+        user_position?
+    } else {
+        user_position.unwrap_or_default()
+    };
+    if let Some(mut user_position) = user_position {
+        msg!("decrease user position deposited_quantity");
+        user_position.remove_deposit(redeemed_reserve_quantity);
+        user_position.exit(ctx.program_id)?;
+    }
+
+    msg!("exit fee: {:?}", exit_fee_quantity);
+    ctx.accounts.tranche_config.add_fee(
+        exit_fee_quantity.iter().sum::<u64>(),
+        &ctx.accounts.protocol_config,
+    )?;
+
+    // check slippage
+    if let Some(min_reserve_out) = input_data.min_reserve_out {
+        if total_reserve_to_redeem < min_reserve_out {
+            msg!(
+                "redeemed reserve {} below the minimum {}",
+                total_reserve_to_redeem,
+                min_reserve_out
+            );
+            return err!(VyperErrorCode::SlippageExceeded);
+        }
+    }
+
     // transfer token from tranche config token account to source account
     msg!("transfer out {}", total_reserve_to_redeem);
     token::transfer(
@@ -194,5 +306,16 @@ pub fn handler(ctx: Context<RedeemContext>, input_data: RedeemInput) -> Result<(
         token::burn(ctx.accounts.junior_burn_to_context(), burn_mint_count[1])?;
     }
 
+    let tranche_data = &ctx.accounts.tranche_config.tranche_data;
+    emit!(Redeemed {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.signer.key(),
+        burned_quantity: burn_mint_count,
+        reserve_quantity: total_reserve_to_redeem,
+        exit_fee_quantity,
+        tranche_fair_value: tranche_data.tranche_fair_value.value,
+        deposited_quantity: tranche_data.deposited_quantity,
+    });
+
     Ok(())
 }
@@ -1,6 +1,7 @@
 use crate::{
     errors::VyperErrorCode,
-    state::{OwnerRestrictedIxFlags, TrancheConfig, TrancheHaltFlags},
+    events::{OrphanedQuantityRouted, TrancheFairValueRefreshed},
+    state::{LastUpdate, OwnerRestrictedIxFlags, ProtocolConfig, TrancheConfig, TrancheHaltFlags},
 };
 use anchor_lang::{
     prelude::*,
@@ -21,9 +22,14 @@ pub struct RefreshTrancheFairValue<'info> {
         has_one = redeem_logic_program_state,
         has_one = senior_tranche_mint,
         has_one = junior_tranche_mint,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion,
     )]
     pub tranche_config: Box<Account<'info, TrancheConfig>>,
 
+    /// Protocol config account, checked for protocol level halts
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
     /// Senior tranche mint
     #[account(mut)]
     pub senior_tranche_mint: Box<Account<'info, Mint>>,
@@ -42,59 +48,170 @@ pub struct RefreshTrancheFairValue<'info> {
 
 impl<'info> RefreshTrancheFairValue<'info> {
     fn are_valid(&self) -> Result<()> {
-        let tranche_data = &self.tranche_config.tranche_data;
-
-        // check that deposits are not halted
-        (!tranche_data
-            .get_halt_flags()?
-            .contains(TrancheHaltFlags::HALT_REFRESHES))
-        .ok_or(VyperErrorCode::HaltError)?;
-
-        // check if the current ix is restricted to owner
-        if tranche_data
-            .get_owner_restricted_ixs()?
-            .contains(OwnerRestrictedIxFlags::REFRESHES)
-        {
-            require_keys_eq!(
-                self.tranche_config.owner,
-                self.signer.key(),
-                VyperErrorCode::OwnerRestrictedIx
-            )
-        }
-
-        Result::Ok(())
+        check_refresh_allowed(
+            &self.tranche_config,
+            &self.protocol_config,
+            self.signer.key(),
+        )
     }
 }
 
 pub fn handler(ctx: Context<RefreshTrancheFairValue>) -> Result<()> {
-    let clock = Clock::get()?;
-
     // check if accounts are valid
     msg!("check if accounts are valid");
     ctx.accounts.are_valid()?;
 
-    let tranche_data = &mut ctx.accounts.tranche_config.tranche_data;
+    refresh_fair_value(
+        &mut ctx.accounts.tranche_config,
+        &ctx.accounts.protocol_config,
+        [
+            ctx.accounts.senior_tranche_mint.supply,
+            ctx.accounts.junior_tranche_mint.supply,
+        ],
+        &ctx.accounts.rate_program_state,
+        &ctx.accounts.redeem_logic_program,
+        &ctx.accounts.redeem_logic_program_state,
+        ctx.accounts.signer.key(),
+    )
+}
+
+/// Check that the signer is allowed to refresh the tranche fair value
+pub fn check_refresh_allowed(
+    tranche_config: &TrancheConfig,
+    protocol_config: &ProtocolConfig,
+    signer: Pubkey,
+) -> Result<()> {
+    let tranche_data = &tranche_config.tranche_data;
+
+    // check that fair values are not final
+    (!tranche_config.settled).ok_or(VyperErrorCode::TrancheSettled)?;
+
+    // check that refreshes are not halted
+    (!tranche_data
+        .get_halt_flags()?
+        .contains(TrancheHaltFlags::HALT_REFRESHES))
+    .ok_or(VyperErrorCode::HaltError)?;
+
+    // check that refreshes are not halted at protocol level
+    (!protocol_config
+        .get_halt_flags()?
+        .contains(TrancheHaltFlags::HALT_REFRESHES))
+    .ok_or(VyperErrorCode::ProtocolHaltError)?;
+
+    // check if the current ix is restricted to owner
+    if tranche_data
+        .get_owner_restricted_ixs()?
+        .contains(OwnerRestrictedIxFlags::REFRESHES)
+    {
+        require_keys_eq!(
+            tranche_config.owner,
+            signer,
+            VyperErrorCode::OwnerRestrictedIx
+        )
+    }
+
+    Result::Ok(())
+}
+
+/// Refresh the tranche fair value when it's stale, using the plugin accounts passed as remaining accounts:
+/// rate_program_state, redeem_logic_program and redeem_logic_program_state of the tranche config, in any order
+pub fn refresh_if_stale<'info>(
+    tranche_config: &mut Account<'info, TrancheConfig>,
+    protocol_config: &ProtocolConfig,
+    tranche_supply: [u64; 2],
+    signer: Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // settled fair values are final and never stale
+    if tranche_config.settled {
+        return Ok(());
+    }
+
+    if !tranche_config
+        .tranche_data
+        .tranche_fair_value
+        .slot_tracking
+        .is_stale(clock.slot, clock.unix_timestamp)?
+    {
+        return Ok(());
+    }
+
+    // the plugin accounts are looked up by key, in any position of the remaining accounts
+    let find_account = |key: Pubkey| {
+        remaining_accounts
+            .iter()
+            .find(|account| account.key() == key)
+            .ok_or(VyperErrorCode::MissingPluginAccounts)
+    };
+    let rate_program_state = find_account(tranche_config.rate_program_state)?;
+    let redeem_logic_program = find_account(tranche_config.redeem_logic_program)?;
+    let redeem_logic_program_state = find_account(tranche_config.redeem_logic_program_state)?;
+
+    msg!("tranche fair value is stale, refreshing");
+    check_refresh_allowed(tranche_config, protocol_config, signer)?;
+    refresh_fair_value(
+        tranche_config,
+        protocol_config,
+        tranche_supply,
+        rate_program_state,
+        redeem_logic_program,
+        redeem_logic_program_state,
+        signer,
+    )?;
+
+    // check that tranche fair values are not stale
+    (!tranche_config
+        .tranche_data
+        .tranche_fair_value
+        .slot_tracking
+        .is_stale(clock.slot, clock.unix_timestamp)?)
+    .ok_or(VyperErrorCode::StaleFairValue)?;
+
+    Ok(())
+}
+
+/// Update reserve and tranche fair values from the rate state and the redeem logic plugin
+pub fn refresh_fair_value<'info>(
+    tranche_config: &mut Account<'info, TrancheConfig>,
+    protocol_config: &ProtocolConfig,
+    tranche_supply: [u64; 2],
+    rate_program_state: &AccountInfo<'info>,
+    redeem_logic_program: &AccountInfo<'info>,
+    redeem_logic_program_state: &AccountInfo<'info>,
+    signer: Pubkey,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let tranche_config_key = tranche_config.key();
+
+    let tranche_data = &mut tranche_config.tranche_data;
 
     // retrieve exchange rate from rate_program
     msg!("deserializing rate state account");
-    let account_data = ctx.accounts.rate_program_state.try_borrow_data()?;
+    let account_data = rate_program_state.try_borrow_data()?;
     let mut account_data_slice: &[u8] = &account_data;
     let rate_state = RateState::try_deserialize_unchecked(&mut account_data_slice)?;
 
     // check if rate state is stale
-    let elapsed_slot = clock
-        .slot
-        .checked_sub(rate_state.refreshed_slot)
-        .ok_or(VyperErrorCode::MathError)?;
-    if elapsed_slot
-        >= tranche_data
-            .reserve_fair_value
-            .slot_tracking
-            .stale_slot_threshold
+    let rate_last_update = LastUpdate::new(
+        rate_state.refreshed_slot,
+        rate_state.refreshed_unix_timestamp,
+    );
+    if tranche_data
+        .reserve_fair_value
+        .slot_tracking
+        .is_stale_since(
+            rate_last_update,
+            LastUpdate::new(clock.slot, clock.unix_timestamp),
+        )?
     {
         return err!(VyperErrorCode::StaleFairValue);
     }
 
+    let old_tranche_fair_value = tranche_data.tranche_fair_value.value;
+    let old_deposited_quantity = tranche_data.deposited_quantity;
+
     // get old and new reserve fair value
     let old_reserve_fair_value = tranche_data.reserve_fair_value.value;
     let new_reserve_fair_value = rate_state.fair_value;
@@ -108,8 +225,8 @@ pub fn handler(ctx: Context<RefreshTrancheFairValue>) -> Result<()> {
     // call execute redeem logic plugin
     msg!("execute redeem logic CPI");
     let cpi_res = cpi_plugin(
-        ctx.accounts.redeem_logic_program.key,
-        ctx.accounts.redeem_logic_program_state.to_account_info(),
+        redeem_logic_program.key,
+        redeem_logic_program_state.to_account_info(),
         RedeemLogicExecuteInput {
             old_reserve_fair_value,
             new_reserve_fair_value,
@@ -120,19 +237,34 @@ pub fn handler(ctx: Context<RefreshTrancheFairValue>) -> Result<()> {
     msg!("cpi return result: {:?}", plugin_result);
 
     msg!("updating fee_to_collect_quantity...");
-    tranche_data.fee_to_collect_quantity = tranche_data
-        .fee_to_collect_quantity
-        .checked_add(plugin_result.fee_quantity)
-        .ok_or(VyperErrorCode::MathError)?;
+    tranche_config.add_fee(plugin_result.fee_quantity, protocol_config)?;
 
     msg!("updating deposited quantity...");
-    tranche_data.deposited_quantity = plugin_result.new_quantity;
+    tranche_config.tranche_data.deposited_quantity = plugin_result.new_quantity;
+
+    // move the quantity assigned to a side without holders, before it's captured by the next depositor
+    let orphaned_quantity =
+        tranche_config.route_orphaned_quantity(tranche_supply, protocol_config)?;
+    if orphaned_quantity.iter().any(|&q| q > 0) {
+        msg!("orphaned quantity: {:?}", orphaned_quantity);
+        emit!(OrphanedQuantityRouted {
+            tranche_config: tranche_config_key,
+            destination: tranche_config.orphaned_quantity_destination,
+            orphaned_quantity,
+        });
+    }
+    let decimals_scale = tranche_config.get_decimals_scale()?;
+    let tranche_data = &mut tranche_config.tranche_data;
 
     msg!("updating tranche fair value...");
-    if ctx.accounts.senior_tranche_mint.supply > 0 {
+    if tranche_supply[0] > 0 {
         let dep_qty = Decimal::from(tranche_data.deposited_quantity[0]);
-        let supply = Decimal::from(ctx.accounts.senior_tranche_mint.supply);
-        let fair_value = dep_qty / supply;
+        let supply = Decimal::from(tranche_supply[0])
+            .checked_mul(decimals_scale)
+            .ok_or(VyperErrorCode::MathError)?;
+        let fair_value = dep_qty
+            .checked_div(supply)
+            .ok_or(VyperErrorCode::MathError)?;
         #[cfg(feature = "debug")]
         {
             msg!("senior dep qty: {:?}", dep_qty);
@@ -141,10 +273,14 @@ pub fn handler(ctx: Context<RefreshTrancheFairValue>) -> Result<()> {
         }
         tranche_data.tranche_fair_value.value[0] = fair_value.serialize();
     }
-    if ctx.accounts.junior_tranche_mint.supply > 0 {
+    if tranche_supply[1] > 0 {
         let dep_qty = Decimal::from(tranche_data.deposited_quantity[1]);
-        let supply = Decimal::from(ctx.accounts.junior_tranche_mint.supply);
-        let fair_value = dep_qty / supply;
+        let supply = Decimal::from(tranche_supply[1])
+            .checked_mul(decimals_scale)
+            .ok_or(VyperErrorCode::MathError)?;
+        let fair_value = dep_qty
+            .checked_div(supply)
+            .ok_or(VyperErrorCode::MathError)?;
         #[cfg(feature = "debug")]
         {
             msg!("junior dep qty: {:?}", dep_qty);
@@ -157,17 +293,31 @@ pub fn handler(ctx: Context<RefreshTrancheFairValue>) -> Result<()> {
         "tranche fair value: {:?}",
         tranche_data.tranche_fair_value.value
     );
-    tranche_data
-        .tranche_fair_value
-        .slot_tracking
-        .update(rate_state.refreshed_slot);
+    tranche_data.tranche_fair_value.slot_tracking.update(
+        rate_state.refreshed_slot,
+        rate_state.refreshed_unix_timestamp,
+    );
 
     msg!("updating reserve fair value...");
     tranche_data.reserve_fair_value.value = rate_state.fair_value;
-    tranche_data
-        .reserve_fair_value
-        .slot_tracking
-        .update(rate_state.refreshed_slot);
+    tranche_data.reserve_fair_value.slot_tracking.update(
+        rate_state.refreshed_slot,
+        rate_state.refreshed_unix_timestamp,
+    );
+
+    let new_tranche_fair_value = tranche_data.tranche_fair_value.value;
+    let new_deposited_quantity = tranche_data.deposited_quantity;
+    emit!(TrancheFairValueRefreshed {
+        tranche_config: tranche_config_key,
+        signer,
+        old_reserve_fair_value,
+        new_reserve_fair_value,
+        old_tranche_fair_value,
+        new_tranche_fair_value,
+        old_deposited_quantity,
+        new_deposited_quantity,
+        fee_quantity: plugin_result.fee_quantity,
+    });
 
     Ok(())
 }
@@ -176,6 +326,7 @@ pub fn handler(ctx: Context<RefreshTrancheFairValue>) -> Result<()> {
 pub struct RateState {
     pub fair_value: [[u8; 16]; 10],
     pub refreshed_slot: u64,
+    pub refreshed_unix_timestamp: i64,
 }
 
 pub fn cpi_plugin(
@@ -0,0 +1,42 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::AllowlistEntryRemoved,
+    state::{AllowlistEntry, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistEntryContext<'info> {
+    /// Tranche config owner or allowlist admin
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(
+        constraint = tranche_config.is_allowlist_authority(authority.key()) @ VyperErrorCode::InvalidAllowlistAuthority,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Allowlist entry to revoke
+    #[account(mut,
+        has_one = tranche_config,
+        close = authority,
+        seeds = [tranche_config.key().as_ref(), b"allowlist".as_ref(), allowlist_entry.user.as_ref()],
+        bump)]
+    pub allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+}
+
+pub fn handler(ctx: Context<RemoveAllowlistEntryContext>) -> Result<()> {
+    msg!(
+        "remove {} from the allowlist",
+        ctx.accounts.allowlist_entry.user
+    );
+
+    emit!(AllowlistEntryRemoved {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.authority.key(),
+        user: ctx.accounts.allowlist_entry.user,
+    });
+
+    Ok(())
+}
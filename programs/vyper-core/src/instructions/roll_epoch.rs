@@ -0,0 +1,96 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::EpochRolled,
+    state::{EpochRecord, PendingEpochRoll, TrancheConfig},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RollEpochContext<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut,
+        has_one = owner,
+        has_one = redeem_logic_program,
+        constraint = tranche_config.settled @ VyperErrorCode::TrancheNotSettled,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Record of the closing epoch
+    #[account(init,
+        payer = owner,
+        space = EpochRecord::LEN,
+        seeds = [tranche_config.key().as_ref(), b"epoch".as_ref(), tranche_config.epoch.to_le_bytes().as_ref()],
+        bump)]
+    pub epoch_record: Box<Account<'info, EpochRecord>>,
+
+    /// Queued epoch roll, closed once applied
+    #[account(mut,
+        has_one = tranche_config,
+        close = owner,
+        seeds = [tranche_config.key().as_ref(), b"pending_epoch_roll".as_ref()],
+        bump)]
+    pub pending_epoch_roll: Box<Account<'info, PendingEpochRoll>>,
+
+    /// CHECK: redeem logic program of the tranche config
+    #[account()]
+    pub redeem_logic_program: AccountInfo<'info>,
+
+    /// CHECK: redeem logic state of the next epoch, owned by the redeem logic program
+    #[account(
+        constraint = next_redeem_logic_program_state.key() == pending_epoch_roll.redeem_logic_program_state @ VyperErrorCode::InvalidInput,
+        owner = redeem_logic_program.key())]
+    pub next_redeem_logic_program_state: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RollEpochContext>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pending_epoch_roll = &ctx.accounts.pending_epoch_roll;
+
+    // check that the timelock expired
+    if clock.slot < pending_epoch_roll.executable_slot {
+        msg!(
+            "epoch roll executable from slot {}, current slot {}",
+            pending_epoch_roll.executable_slot,
+            clock.slot
+        );
+        return err!(VyperErrorCode::TimelockNotExpired);
+    }
+
+    // store the settled values of the closing epoch
+    let tranche_config = &mut ctx.accounts.tranche_config;
+    let epoch_record = &mut ctx.accounts.epoch_record;
+    msg!("close epoch {}", tranche_config.epoch);
+    epoch_record.tranche_config = tranche_config.key();
+    epoch_record.epoch = tranche_config.epoch;
+    epoch_record.redeem_logic_program_state = tranche_config.redeem_logic_program_state;
+    epoch_record.reserve_fair_value = tranche_config.tranche_data.reserve_fair_value.value;
+    epoch_record.tranche_fair_value = tranche_config.tranche_data.tranche_fair_value.value;
+    epoch_record.deposited_quantity = tranche_config.tranche_data.deposited_quantity;
+    epoch_record.rolled_at = clock.unix_timestamp;
+
+    // open the next epoch, deposited quantities and tranche supplies carry over
+    tranche_config.epoch = tranche_config
+        .epoch
+        .checked_add(1)
+        .ok_or(VyperErrorCode::MathError)?;
+    tranche_config.redeem_logic_program_state = pending_epoch_roll.redeem_logic_program_state;
+    tranche_config.lifecycle = pending_epoch_roll.lifecycle;
+    tranche_config.settled = false;
+    msg!("open epoch {}", tranche_config.epoch);
+
+    emit!(EpochRolled {
+        tranche_config: tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        epoch: tranche_config.epoch,
+        old_redeem_logic_program_state: epoch_record.redeem_logic_program_state,
+        new_redeem_logic_program_state: tranche_config.redeem_logic_program_state,
+        lifecycle: tranche_config.lifecycle,
+    });
+
+    Ok(())
+}
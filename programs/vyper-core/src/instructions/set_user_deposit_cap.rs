@@ -0,0 +1,84 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::UserDepositCapUpdated,
+    state::{TrancheConfig, UserPosition},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetUserDepositCapContext<'info> {
+    pub owner: Signer<'info>,
+
+    /// Tranche config account, where all the parameters are saved
+    #[account(mut, has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// User position to update
+    #[account(mut,
+        has_one = tranche_config,
+        seeds = [tranche_config.key().as_ref(), b"user_position".as_ref(), user_position.user.as_ref()],
+        bump)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+}
+
+pub fn handler(
+    ctx: Context<SetUserDepositCapContext>,
+    deposit_cap: Option<[Option<u64>; 2]>,
+) -> Result<()> {
+    let user_position = &mut ctx.accounts.user_position;
+
+    msg!("update user_position deposit_cap_override");
+
+    let old_value = user_position.deposit_cap_override;
+    let old_wallet_deposit_cap = ctx
+        .accounts
+        .tranche_config
+        .get_wallet_deposit_cap(user_position);
+
+    #[cfg(feature = "debug")]
+    msg!("+ old value: {:?}", user_position.deposit_cap_override);
+
+    user_position.deposit_cap_override = deposit_cap;
+
+    #[cfg(feature = "debug")]
+    msg!("+ new value: {:?}", user_position.deposit_cap_override);
+
+    // raised or removed caps have to wait for the timelock, there is no queue for user positions
+    let new_wallet_deposit_cap = ctx
+        .accounts
+        .tranche_config
+        .get_wallet_deposit_cap(user_position);
+    if ctx.accounts.tranche_config.timelock_slots > 0
+        && is_loosening(old_wallet_deposit_cap, new_wallet_deposit_cap)
+    {
+        msg!("raising a user deposit cap requires a zero timelock");
+        return err!(VyperErrorCode::TimelockRequired);
+    }
+
+    // once an override is set, deposits can't skip the user position
+    if deposit_cap.is_some() {
+        ctx.accounts.tranche_config.has_user_deposit_cap_override = true;
+    }
+
+    emit!(UserDepositCapUpdated {
+        tranche_config: ctx.accounts.tranche_config.key(),
+        signer: ctx.accounts.owner.key(),
+        user: user_position.user,
+        old_value,
+        new_value: user_position.deposit_cap_override,
+    });
+
+    Ok(())
+}
+
+/// Check if any side cap is raised or removed
+fn is_loosening(old_deposit_cap: [Option<u64>; 2], new_deposit_cap: [Option<u64>; 2]) -> bool {
+    old_deposit_cap
+        .iter()
+        .zip(new_deposit_cap.iter())
+        .any(|(old_cap, new_cap)| match (old_cap, new_cap) {
+            (Some(old_cap), Some(new_cap)) => new_cap > old_cap,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+}
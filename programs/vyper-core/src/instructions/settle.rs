@@ -0,0 +1,96 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::TrancheSettled,
+    instructions::{check_refresh_allowed, refresh_fair_value},
+    state::{ProtocolConfig, TrancheConfig, TranchePhase},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use boolinator::Boolinator;
+
+#[derive(Accounts)]
+pub struct SettleContext<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        has_one = rate_program_state,
+        has_one = redeem_logic_program,
+        has_one = redeem_logic_program_state,
+        has_one = senior_tranche_mint,
+        has_one = junior_tranche_mint,
+        constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion,
+    )]
+    pub tranche_config: Box<Account<'info, TrancheConfig>>,
+
+    /// Protocol config account, checked for protocol level halts
+    #[account(seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+
+    /// Senior tranche mint
+    pub senior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// Junior tranche mint
+    pub junior_tranche_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK:
+    pub rate_program_state: AccountInfo<'info>,
+    /// CHECK:
+    pub redeem_logic_program: AccountInfo<'info>,
+    /// CHECK:
+    pub redeem_logic_program_state: AccountInfo<'info>,
+}
+
+impl<'info> SettleContext<'info> {
+    fn are_valid(&self, unix_timestamp: i64) -> Result<()> {
+        // check that the maturity is reached
+        (self.tranche_config.lifecycle.get_phase(unix_timestamp) == TranchePhase::Matured)
+            .ok_or(VyperErrorCode::TrancheNotMatured)?;
+
+        // check that the tranche is not settled and the final refresh is allowed
+        check_refresh_allowed(
+            &self.tranche_config,
+            &self.protocol_config,
+            self.signer.key(),
+        )
+    }
+}
+
+pub fn handler(ctx: Context<SettleContext>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // check if accounts are valid
+    msg!("check if accounts are valid");
+    ctx.accounts.are_valid(clock.unix_timestamp)?;
+
+    // final refresh
+    msg!("final refresh");
+    refresh_fair_value(
+        &mut ctx.accounts.tranche_config,
+        &ctx.accounts.protocol_config,
+        [
+            ctx.accounts.senior_tranche_mint.supply,
+            ctx.accounts.junior_tranche_mint.supply,
+        ],
+        &ctx.accounts.rate_program_state,
+        &ctx.accounts.redeem_logic_program,
+        &ctx.accounts.redeem_logic_program_state,
+        ctx.accounts.signer.key(),
+    )?;
+
+    // freeze the fair values
+    msg!("settle tranche");
+    let tranche_config = &mut ctx.accounts.tranche_config;
+    tranche_config.settled = true;
+
+    let tranche_data = &tranche_config.tranche_data;
+    emit!(TrancheSettled {
+        tranche_config: tranche_config.key(),
+        signer: ctx.accounts.signer.key(),
+        reserve_fair_value: tranche_data.reserve_fair_value.value,
+        tranche_fair_value: tranche_data.tranche_fair_value.value,
+        deposited_quantity: tranche_data.deposited_quantity,
+        settled_at: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
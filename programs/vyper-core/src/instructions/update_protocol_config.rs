@@ -0,0 +1,151 @@
+use crate::{
+    errors::VyperErrorCode,
+    events::{
+        ProtocolAdminUpdated, ProtocolFeeBpsUpdated, ProtocolHaltFlagsUpdated,
+        ProtocolTreasuryUpdated,
+    },
+    state::ProtocolConfig,
+};
+use anchor_lang::prelude::*;
+
+bitflags::bitflags! {
+    struct UpdateProtocolConfigFlags: u16 {
+        const ADMIN = 1 << 0;
+        const PROTOCOL_FEE_BPS = 1 << 1;
+        const TREASURY = 1 << 2;
+        const HALT_FLAGS = 1 << 3;
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfigContext<'info> {
+    pub admin: Signer<'info>,
+
+    /// Protocol config account, a single PDA for the whole program
+    #[account(mut, has_one = admin, seeds = [b"protocol_config".as_ref()], bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
+pub struct UpdateProtocolConfigInput {
+    pub bitmask: u16,
+    pub admin: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub treasury: Pubkey,
+    pub halt_flags: u16,
+}
+
+impl UpdateProtocolConfigInput {
+    fn get_update_protocol_bitmask(&self) -> Option<UpdateProtocolConfigFlags> {
+        UpdateProtocolConfigFlags::from_bits(self.bitmask)
+    }
+}
+
+pub fn handler(
+    ctx: Context<UpdateProtocolConfigContext>,
+    input_data: UpdateProtocolConfigInput,
+) -> Result<()> {
+    // update protocol config account
+
+    let protocol_config_key = ctx.accounts.protocol_config.key();
+    let signer = ctx.accounts.admin.key();
+    let protocol_config = &mut ctx.accounts.protocol_config;
+
+    let update_bitmask = input_data
+        .get_update_protocol_bitmask()
+        .ok_or(VyperErrorCode::InvalidBitmask)?;
+
+    // admin
+
+    if update_bitmask.contains(UpdateProtocolConfigFlags::ADMIN) {
+        msg!("update protocol_config admin");
+
+        let old_value = protocol_config.admin;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", protocol_config.admin);
+
+        protocol_config.admin = input_data.admin;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", protocol_config.admin);
+
+        emit!(ProtocolAdminUpdated {
+            protocol_config: protocol_config_key,
+            signer,
+            old_value,
+            new_value: protocol_config.admin,
+        });
+    }
+
+    // protocol fee bps
+
+    if update_bitmask.contains(UpdateProtocolConfigFlags::PROTOCOL_FEE_BPS) {
+        msg!("update protocol_config protocol_fee_bps");
+
+        let old_value = protocol_config.protocol_fee_bps;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", protocol_config.protocol_fee_bps);
+
+        protocol_config.set_protocol_fee_bps(input_data.protocol_fee_bps)?;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", protocol_config.protocol_fee_bps);
+
+        emit!(ProtocolFeeBpsUpdated {
+            protocol_config: protocol_config_key,
+            signer,
+            old_value,
+            new_value: protocol_config.protocol_fee_bps,
+        });
+    }
+
+    // treasury
+
+    if update_bitmask.contains(UpdateProtocolConfigFlags::TREASURY) {
+        msg!("update protocol_config treasury");
+
+        let old_value = protocol_config.treasury;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", protocol_config.treasury);
+
+        protocol_config.treasury = input_data.treasury;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", protocol_config.treasury);
+
+        emit!(ProtocolTreasuryUpdated {
+            protocol_config: protocol_config_key,
+            signer,
+            old_value,
+            new_value: protocol_config.treasury,
+        });
+    }
+
+    // halt flags
+
+    if update_bitmask.contains(UpdateProtocolConfigFlags::HALT_FLAGS) {
+        msg!("update protocol_config halt_flags");
+
+        let old_value = protocol_config.get_halt_flags()?.bits();
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", protocol_config.get_halt_flags()?.bits());
+
+        protocol_config.set_halt_flags(input_data.halt_flags)?;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", protocol_config.get_halt_flags()?.bits());
+
+        emit!(ProtocolHaltFlagsUpdated {
+            protocol_config: protocol_config_key,
+            signer,
+            old_value,
+            new_value: protocol_config.get_halt_flags()?.bits(),
+        });
+    }
+
+    Ok(())
+}
@@ -1,13 +1,51 @@
-use crate::{errors::VyperErrorCode, state::TrancheConfig};
+use crate::{
+    errors::VyperErrorCode,
+    events::{
+        AllowlistAdminUpdated, AllowlistRestrictedIxsUpdated, CoverageRatioBpsBoundsUpdated,
+        DeadSharesQuantityUpdated, DepositCapModeUpdated, DepositCapUpdated, EntryFeeBpsUpdated,
+        ExitFeeBpsUpdated, GuardianUpdated, HaltFlagsUpdated, LifecycleUpdated,
+        MinInitialDepositUpdated, OrphanedQuantityDestinationUpdated, OwnerRestrictedIxsUpdated,
+        ReserveFairValueStaleSecondsThresholdUpdated, ReserveFairValueStaleSlotThresholdUpdated,
+        ReserveFairValueStalenessModeUpdated, ReserveSurplusDestinationUpdated,
+        TimelockSlotsUpdated, TotalDepositCapUpdated, TrancheFairValueStaleSecondsThresholdUpdated,
+        TrancheFairValueStaleSlotThresholdUpdated, TrancheFairValueStalenessModeUpdated,
+        WalletDepositCapUpdated,
+    },
+    state::{
+        AllowlistRestrictedIxFlags, DepositCapMode, OrphanedQuantityDestination,
+        OwnerRestrictedIxFlags, ReserveSurplusDestination, StalenessMode, TrancheConfig,
+        TrancheHaltFlags, TrancheLifecycle,
+    },
+};
 use anchor_lang::prelude::*;
+use boolinator::Boolinator;
 
 bitflags::bitflags! {
-    struct UpdateTrancheConfigFlags: u16 {
+    struct UpdateTrancheConfigFlags: u32 {
         const HALT_FLAGS = 1 << 0;
         const OWNER_RESTRICTED_IXS = 1 << 1;
         const RESERVE_FAIR_VALUE_STALE_SLOT_THRESHOLD = 1 << 2;
         const TRANCHE_FAIR_VALUE_STALE_SLOT_THRESHOLD = 1 << 3;
         const DEPOSIT_CAP = 1 << 4;
+        const TIMELOCK_SLOTS = 1 << 5;
+        const GUARDIAN = 1 << 6;
+        const RESERVE_SURPLUS_DESTINATION = 1 << 7;
+        const ENTRY_FEE_BPS = 1 << 8;
+        const EXIT_FEE_BPS = 1 << 9;
+        const ALLOWLIST_RESTRICTED_IXS = 1 << 10;
+        const ALLOWLIST_ADMIN = 1 << 11;
+        const WALLET_DEPOSIT_CAP = 1 << 12;
+        const DEPOSIT_CAP_MODE = 1 << 13;
+        const TOTAL_DEPOSIT_CAP = 1 << 14;
+        const COVERAGE_RATIO_BPS_BOUNDS = 1 << 15;
+        const LIFECYCLE = 1 << 16;
+        const RESERVE_FAIR_VALUE_STALE_SECONDS_THRESHOLD = 1 << 17;
+        const TRANCHE_FAIR_VALUE_STALE_SECONDS_THRESHOLD = 1 << 18;
+        const RESERVE_FAIR_VALUE_STALENESS_MODE = 1 << 19;
+        const TRANCHE_FAIR_VALUE_STALENESS_MODE = 1 << 20;
+        const ORPHANED_QUANTITY_DESTINATION = 1 << 21;
+        const MIN_INITIAL_DEPOSIT = 1 << 22;
+        const DEAD_SHARES_QUANTITY = 1 << 23;
     }
 }
 
@@ -16,43 +54,351 @@ pub struct UpdateTrancheDataContext<'info> {
     pub owner: Signer<'info>,
 
     /// Tranche config account, where all the parameters are saved
-    #[account(mut, has_one = owner)]
+    #[account(mut, has_one = owner, constraint = tranche_config.is_supported_version() @ VyperErrorCode::UnsupportedTrancheConfigVersion)]
     pub tranche_config: Box<Account<'info, TrancheConfig>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Default, Clone)]
 pub struct UpdateTrancheDataInput {
+    /// update flags from HALT_FLAGS to COVERAGE_RATIO_BPS_BOUNDS
     pub bitmask: u16,
     pub halt_flags: u16,
     pub owner_restricted_ixs: u16,
     pub reserve_fair_value_stale_slot_threshold: u64,
     pub tranche_fair_value_stale_slot_threshold: u64,
     pub deposit_cap: [Option<u64>; 2],
+    pub timelock_slots: u64,
+    pub guardian: Option<Pubkey>,
+    pub reserve_surplus_destination: ReserveSurplusDestination,
+    pub entry_fee_bps: [u16; 2],
+    pub exit_fee_bps: [u16; 2],
+    pub allowlist_restricted_ixs: u16,
+    pub allowlist_admin: Option<Pubkey>,
+    pub wallet_deposit_cap: [Option<u64>; 2],
+    pub deposit_cap_mode: DepositCapMode,
+    pub total_deposit_cap: Option<u64>,
+    pub coverage_ratio_bps_bounds: [Option<u64>; 2],
+    pub lifecycle: TrancheLifecycle,
+    pub reserve_fair_value_stale_seconds_threshold: u64,
+    pub tranche_fair_value_stale_seconds_threshold: u64,
+    pub reserve_fair_value_staleness_mode: StalenessMode,
+    pub tranche_fair_value_staleness_mode: StalenessMode,
+    pub orphaned_quantity_destination: OrphanedQuantityDestination,
+    pub min_initial_deposit: [u64; 2],
+    pub dead_shares_quantity: u64,
+
+    /// update flags from LIFECYCLE onward, shifted by 16 bits since bitmask has room for 16 flags only
+    pub bitmask_ext: u16,
 }
 
 impl UpdateTrancheDataInput {
-    fn get_update_tranche_bitmask(&self) -> Option<UpdateTrancheConfigFlags> {
-        UpdateTrancheConfigFlags::from_bits(self.bitmask)
+    fn get_update_tranche_bitmask(&self) -> Result<UpdateTrancheConfigFlags> {
+        UpdateTrancheConfigFlags::from_bits((self.bitmask_ext as u32) << 16 | self.bitmask as u32)
+            .ok_or_else(|| VyperErrorCode::InvalidBitmask.into())
     }
+
+    /// Check if the selected changes loosen the tranche constraints for the owner:
+    /// - lifted halt flags, owner restrictions or allowlist restrictions
+    /// - higher stale slot or seconds thresholds, any staleness mode change
+    /// - higher side, wallet or total deposit caps, any deposit cap mode change
+    /// - a shorter timelock, a removed or replaced guardian, an added or replaced allowlist admin
+    /// - higher entry or exit fees, the reserve surplus or the orphaned quantity routed to fees
+    /// - wider coverage ratio bounds, any lifecycle change
+    /// - a lower minimum initial deposit or fewer dead shares
+    pub fn is_loosening(&self, tranche_config: &TrancheConfig) -> Result<bool> {
+        let tranche_data = &tranche_config.tranche_data;
+        let update_bitmask = self.get_update_tranche_bitmask()?;
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::HALT_FLAGS)
+            && !TrancheHaltFlags::from_bits(self.halt_flags)
+                .ok_or(VyperErrorCode::InvalidTrancheHaltFlags)?
+                .contains(tranche_data.get_halt_flags()?)
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::OWNER_RESTRICTED_IXS)
+            && !OwnerRestrictedIxFlags::from_bits(self.owner_restricted_ixs)
+                .ok_or(VyperErrorCode::InvalidOwnerRestrictedIxFlags)?
+                .contains(tranche_data.get_owner_restricted_ixs()?)
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask
+            .contains(UpdateTrancheConfigFlags::RESERVE_FAIR_VALUE_STALE_SLOT_THRESHOLD)
+            && self.reserve_fair_value_stale_slot_threshold
+                > tranche_data
+                    .reserve_fair_value
+                    .slot_tracking
+                    .stale_slot_threshold
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask
+            .contains(UpdateTrancheConfigFlags::TRANCHE_FAIR_VALUE_STALE_SLOT_THRESHOLD)
+            && self.tranche_fair_value_stale_slot_threshold
+                > tranche_data
+                    .tranche_fair_value
+                    .slot_tracking
+                    .stale_slot_threshold
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask
+            .contains(UpdateTrancheConfigFlags::RESERVE_FAIR_VALUE_STALE_SECONDS_THRESHOLD)
+            && self.reserve_fair_value_stale_seconds_threshold
+                > tranche_data
+                    .reserve_fair_value
+                    .slot_tracking
+                    .stale_seconds_threshold
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask
+            .contains(UpdateTrancheConfigFlags::TRANCHE_FAIR_VALUE_STALE_SECONDS_THRESHOLD)
+            && self.tranche_fair_value_stale_seconds_threshold
+                > tranche_data
+                    .tranche_fair_value
+                    .slot_tracking
+                    .stale_seconds_threshold
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::RESERVE_FAIR_VALUE_STALENESS_MODE)
+            && self.reserve_fair_value_staleness_mode
+                != tranche_data.reserve_fair_value.slot_tracking.staleness_mode
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::TRANCHE_FAIR_VALUE_STALENESS_MODE)
+            && self.tranche_fair_value_staleness_mode
+                != tranche_data.tranche_fair_value.slot_tracking.staleness_mode
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::DEPOSIT_CAP) {
+            for i in 0..self.deposit_cap.len() {
+                let is_raised = match (tranche_data.deposit_cap[i], self.deposit_cap[i]) {
+                    (Some(old_cap), Some(new_cap)) => new_cap > old_cap,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                };
+                if is_raised {
+                    return Ok(true);
+                }
+            }
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::WALLET_DEPOSIT_CAP) {
+            for i in 0..self.wallet_deposit_cap.len() {
+                let is_raised = match (
+                    tranche_config.wallet_deposit_cap[i],
+                    self.wallet_deposit_cap[i],
+                ) {
+                    (Some(old_cap), Some(new_cap)) => new_cap > old_cap,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                };
+                if is_raised {
+                    return Ok(true);
+                }
+            }
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::DEPOSIT_CAP_MODE)
+            && self.deposit_cap_mode != tranche_config.deposit_cap_mode
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::TOTAL_DEPOSIT_CAP) {
+            let is_raised = match (tranche_config.total_deposit_cap, self.total_deposit_cap) {
+                (Some(old_cap), Some(new_cap)) => new_cap > old_cap,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if is_raised {
+                return Ok(true);
+            }
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::COVERAGE_RATIO_BPS_BOUNDS) {
+            let [old_min, old_max] = tranche_config.get_coverage_ratio_bps_bounds();
+            let [new_min, new_max] = self.coverage_ratio_bps_bounds;
+            let is_min_lowered = match (old_min, new_min) {
+                (Some(old_bound), Some(new_bound)) => new_bound < old_bound,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            let is_max_raised = match (old_max, new_max) {
+                (Some(old_bound), Some(new_bound)) => new_bound > old_bound,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if is_min_lowered || is_max_raised {
+                return Ok(true);
+            }
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::LIFECYCLE)
+            && self.lifecycle != tranche_config.lifecycle
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::TIMELOCK_SLOTS)
+            && self.timelock_slots < tranche_config.timelock_slots
+        {
+            return Ok(true);
+        }
+
+        // a removed or replaced guardian, only adding one is immediate
+        if update_bitmask.contains(UpdateTrancheConfigFlags::GUARDIAN)
+            && tranche_config.guardian.is_some()
+            && self.guardian != tranche_config.guardian
+        {
+            return Ok(true);
+        }
+
+        // an added or replaced allowlist admin, only removing it is immediate
+        if update_bitmask.contains(UpdateTrancheConfigFlags::ALLOWLIST_ADMIN)
+            && self.allowlist_admin.is_some()
+            && self.allowlist_admin != tranche_config.allowlist_admin
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::RESERVE_SURPLUS_DESTINATION)
+            && self.reserve_surplus_destination == ReserveSurplusDestination::Fee
+            && tranche_config.reserve_surplus_destination != ReserveSurplusDestination::Fee
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::ORPHANED_QUANTITY_DESTINATION)
+            && self.orphaned_quantity_destination == OrphanedQuantityDestination::Fee
+            && tranche_config.orphaned_quantity_destination != OrphanedQuantityDestination::Fee
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::MIN_INITIAL_DEPOSIT)
+            && self
+                .min_initial_deposit
+                .iter()
+                .zip(tranche_config.min_initial_deposit.iter())
+                .any(|(new_min, old_min)| new_min < old_min)
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::DEAD_SHARES_QUANTITY)
+            && self.dead_shares_quantity < tranche_config.dead_shares_quantity
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::ENTRY_FEE_BPS)
+            && self
+                .entry_fee_bps
+                .iter()
+                .zip(tranche_data.get_entry_fee_bps().iter())
+                .any(|(new_fee, old_fee)| new_fee > old_fee)
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::EXIT_FEE_BPS)
+            && self
+                .exit_fee_bps
+                .iter()
+                .zip(tranche_data.get_exit_fee_bps().iter())
+                .any(|(new_fee, old_fee)| new_fee > old_fee)
+        {
+            return Ok(true);
+        }
+
+        if update_bitmask.contains(UpdateTrancheConfigFlags::ALLOWLIST_RESTRICTED_IXS)
+            && !AllowlistRestrictedIxFlags::from_bits(self.allowlist_restricted_ixs)
+                .ok_or(VyperErrorCode::InvalidAllowlistRestrictedIxFlags)?
+                .contains(tranche_config.get_allowlist_restricted_ixs()?)
+        {
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    pub const LEN: usize = 2 + // pub bitmask: u16,
+    2 + // pub halt_flags: u16,
+    2 + // pub owner_restricted_ixs: u16,
+    8 + // pub reserve_fair_value_stale_slot_threshold: u64,
+    8 + // pub tranche_fair_value_stale_slot_threshold: u64,
+    2 * (1 + 8) + // pub deposit_cap: [Option<u64>; 2],
+    8 + // pub timelock_slots: u64,
+    1 + 32 + // pub guardian: Option<Pubkey>,
+    1 + // pub reserve_surplus_destination: ReserveSurplusDestination,
+    2 * 2 + // pub entry_fee_bps: [u16; 2],
+    2 * 2 + // pub exit_fee_bps: [u16; 2],
+    2 + // pub allowlist_restricted_ixs: u16,
+    1 + 32 + // pub allowlist_admin: Option<Pubkey>,
+    2 * (1 + 8) + // pub wallet_deposit_cap: [Option<u64>; 2],
+    1 + // pub deposit_cap_mode: DepositCapMode,
+    1 + 8 + // pub total_deposit_cap: Option<u64>,
+    2 * (1 + 8) + // pub coverage_ratio_bps_bounds: [Option<u64>; 2],
+    TrancheLifecycle::LEN + // pub lifecycle: TrancheLifecycle,
+    8 + // pub reserve_fair_value_stale_seconds_threshold: u64,
+    8 + // pub tranche_fair_value_stale_seconds_threshold: u64,
+    1 + // pub reserve_fair_value_staleness_mode: StalenessMode,
+    1 + // pub tranche_fair_value_staleness_mode: StalenessMode,
+    1 + // pub orphaned_quantity_destination: OrphanedQuantityDestination,
+    2 * 8 + // pub min_initial_deposit: [u64; 2],
+    8 + // pub dead_shares_quantity: u64,
+    2; // pub bitmask_ext: u16,
 }
 
 pub fn handler(
     ctx: Context<UpdateTrancheDataContext>,
     input_data: UpdateTrancheDataInput,
+) -> Result<()> {
+    let tranche_config = &mut ctx.accounts.tranche_config;
+
+    // loosening changes have to go through the timelock queue
+    if tranche_config.timelock_slots > 0 && input_data.is_loosening(tranche_config)? {
+        msg!("loosening changes need to be queued");
+        return err!(VyperErrorCode::TimelockRequired);
+    }
+
+    update_tranche_config(tranche_config, ctx.accounts.owner.key(), &input_data)
+}
+
+/// Apply the selected changes to the tranche config
+pub fn update_tranche_config(
+    tranche_config: &mut Account<TrancheConfig>,
+    signer: Pubkey,
+    input_data: &UpdateTrancheDataInput,
 ) -> Result<()> {
     // update tranche config account
 
-    let tranche_data = &mut ctx.accounts.tranche_config.tranche_data;
+    let tranche_config_key = tranche_config.key();
+    let tranche_data = &mut tranche_config.tranche_data;
 
-    let update_bitmask = input_data
-        .get_update_tranche_bitmask()
-        .ok_or(VyperErrorCode::InvalidBitmask)?;
+    let update_bitmask = input_data.get_update_tranche_bitmask()?;
 
     // halt flags
 
     if update_bitmask.contains(UpdateTrancheConfigFlags::HALT_FLAGS) {
         msg!("update tranche_data halt_flags");
 
+        let old_value = tranche_data.get_halt_flags()?.bits();
+
         #[cfg(feature = "debug")]
         msg!("+ old value: {}", tranche_data.get_halt_flags()?.bits());
 
@@ -60,6 +406,13 @@ pub fn handler(
 
         #[cfg(feature = "debug")]
         msg!("+ new value: {}", tranche_data.get_halt_flags()?.bits());
+
+        emit!(HaltFlagsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data.get_halt_flags()?.bits(),
+        });
     }
 
     // owner restricted ixs
@@ -67,6 +420,8 @@ pub fn handler(
     if update_bitmask.contains(UpdateTrancheConfigFlags::OWNER_RESTRICTED_IXS) {
         msg!("update tranche_data owner_restricted_ixs");
 
+        let old_value = tranche_data.get_owner_restricted_ixs()?.bits();
+
         #[cfg(feature = "debug")]
         msg!(
             "+ old value: {}",
@@ -80,6 +435,13 @@ pub fn handler(
             "+ new value: {}",
             tranche_data.get_owner_restricted_ixs()?.bits()
         );
+
+        emit!(OwnerRestrictedIxsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data.get_owner_restricted_ixs()?.bits(),
+        });
     }
 
     // reserve fair value stale slot th
@@ -87,6 +449,11 @@ pub fn handler(
     if update_bitmask.contains(UpdateTrancheConfigFlags::RESERVE_FAIR_VALUE_STALE_SLOT_THRESHOLD) {
         msg!("update tranche_data reserve_fair_value stale_slot_threashold");
 
+        let old_value = tranche_data
+            .reserve_fair_value
+            .slot_tracking
+            .stale_slot_threshold;
+
         #[cfg(feature = "debug")]
         msg!(
             "+ old value: {}",
@@ -109,6 +476,16 @@ pub fn handler(
                 .slot_tracking
                 .stale_slot_threshold
         );
+
+        emit!(ReserveFairValueStaleSlotThresholdUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data
+                .reserve_fair_value
+                .slot_tracking
+                .stale_slot_threshold,
+        });
     }
 
     // tranche fair value stale slot th
@@ -116,6 +493,11 @@ pub fn handler(
     if update_bitmask.contains(UpdateTrancheConfigFlags::TRANCHE_FAIR_VALUE_STALE_SLOT_THRESHOLD) {
         msg!("update tranche_data tranche_fair_value stale_slot_threashold");
 
+        let old_value = tranche_data
+            .tranche_fair_value
+            .slot_tracking
+            .stale_slot_threshold;
+
         #[cfg(feature = "debug")]
         msg!(
             "+ old value: {}",
@@ -138,6 +520,16 @@ pub fn handler(
                 .slot_tracking
                 .stale_slot_threshold
         );
+
+        emit!(TrancheFairValueStaleSlotThresholdUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data
+                .tranche_fair_value
+                .slot_tracking
+                .stale_slot_threshold,
+        });
     }
 
     // deposit cap
@@ -145,6 +537,8 @@ pub fn handler(
     if update_bitmask.contains(UpdateTrancheConfigFlags::DEPOSIT_CAP) {
         msg!("update tranche_data deposit_cap");
 
+        let old_value = tranche_data.deposit_cap;
+
         #[cfg(feature = "debug")]
         msg!("+ old value: {:?}", tranche_data.deposit_cap);
 
@@ -152,6 +546,487 @@ pub fn handler(
 
         #[cfg(feature = "debug")]
         msg!("+ old value: {:?}", tranche_data.deposit_cap);
+
+        emit!(DepositCapUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data.deposit_cap,
+        });
+    }
+
+    // entry fee bps
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::ENTRY_FEE_BPS) {
+        msg!("update tranche_data entry_fee_bps");
+
+        let old_value = tranche_data.get_entry_fee_bps();
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_data.get_entry_fee_bps());
+
+        tranche_data.set_entry_fee_bps(input_data.entry_fee_bps)?;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_data.get_entry_fee_bps());
+
+        emit!(EntryFeeBpsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data.get_entry_fee_bps(),
+        });
+    }
+
+    // exit fee bps
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::EXIT_FEE_BPS) {
+        msg!("update tranche_data exit_fee_bps");
+
+        let old_value = tranche_data.get_exit_fee_bps();
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_data.get_exit_fee_bps());
+
+        tranche_data.set_exit_fee_bps(input_data.exit_fee_bps)?;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_data.get_exit_fee_bps());
+
+        emit!(ExitFeeBpsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_data.get_exit_fee_bps(),
+        });
+    }
+
+    // timelock slots
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::TIMELOCK_SLOTS) {
+        msg!("update tranche_config timelock_slots");
+
+        let old_value = tranche_config.timelock_slots;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", tranche_config.timelock_slots);
+
+        tranche_config.timelock_slots = input_data.timelock_slots;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", tranche_config.timelock_slots);
+
+        emit!(TimelockSlotsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.timelock_slots,
+        });
+    }
+
+    // guardian
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::GUARDIAN) {
+        msg!("update tranche_config guardian");
+
+        let old_value = tranche_config.guardian;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.guardian);
+
+        tranche_config.guardian = input_data.guardian;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.guardian);
+
+        emit!(GuardianUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.guardian,
+        });
+    }
+
+    // reserve surplus destination
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::RESERVE_SURPLUS_DESTINATION) {
+        msg!("update tranche_config reserve_surplus_destination");
+
+        let old_value = tranche_config.reserve_surplus_destination;
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ old value: {:?}",
+            tranche_config.reserve_surplus_destination
+        );
+
+        tranche_config.reserve_surplus_destination = input_data.reserve_surplus_destination;
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ new value: {:?}",
+            tranche_config.reserve_surplus_destination
+        );
+
+        emit!(ReserveSurplusDestinationUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.reserve_surplus_destination,
+        });
+    }
+
+    // allowlist restricted ixs
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::ALLOWLIST_RESTRICTED_IXS) {
+        msg!("update tranche_config allowlist_restricted_ixs");
+
+        let old_value = tranche_config.get_allowlist_restricted_ixs()?.bits();
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ old value: {}",
+            tranche_config.get_allowlist_restricted_ixs()?.bits()
+        );
+
+        tranche_config.set_allowlist_restricted_ixs(input_data.allowlist_restricted_ixs)?;
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ new value: {}",
+            tranche_config.get_allowlist_restricted_ixs()?.bits()
+        );
+
+        emit!(AllowlistRestrictedIxsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.get_allowlist_restricted_ixs()?.bits(),
+        });
+    }
+
+    // allowlist admin
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::ALLOWLIST_ADMIN) {
+        msg!("update tranche_config allowlist_admin");
+
+        let old_value = tranche_config.allowlist_admin;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.allowlist_admin);
+
+        tranche_config.allowlist_admin = input_data.allowlist_admin;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.allowlist_admin);
+
+        emit!(AllowlistAdminUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.allowlist_admin,
+        });
+    }
+
+    // wallet deposit cap
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::WALLET_DEPOSIT_CAP) {
+        msg!("update tranche_config wallet_deposit_cap");
+
+        let old_value = tranche_config.wallet_deposit_cap;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.wallet_deposit_cap);
+
+        tranche_config.wallet_deposit_cap = input_data.wallet_deposit_cap;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.wallet_deposit_cap);
+
+        emit!(WalletDepositCapUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.wallet_deposit_cap,
+        });
+    }
+
+    // deposit cap mode
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::DEPOSIT_CAP_MODE) {
+        msg!("update tranche_config deposit_cap_mode");
+
+        let old_value = tranche_config.deposit_cap_mode;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.deposit_cap_mode);
+
+        tranche_config.deposit_cap_mode = input_data.deposit_cap_mode;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.deposit_cap_mode);
+
+        emit!(DepositCapModeUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.deposit_cap_mode,
+        });
+    }
+
+    // total deposit cap
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::TOTAL_DEPOSIT_CAP) {
+        msg!("update tranche_config total_deposit_cap");
+
+        let old_value = tranche_config.total_deposit_cap;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.total_deposit_cap);
+
+        tranche_config.total_deposit_cap = input_data.total_deposit_cap;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.total_deposit_cap);
+
+        emit!(TotalDepositCapUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.total_deposit_cap,
+        });
+    }
+
+    // coverage ratio bps bounds
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::COVERAGE_RATIO_BPS_BOUNDS) {
+        msg!("update tranche_config coverage_ratio_bps_bounds");
+
+        let old_value = tranche_config.get_coverage_ratio_bps_bounds();
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ old value: {:?}",
+            tranche_config.get_coverage_ratio_bps_bounds()
+        );
+
+        tranche_config.set_coverage_ratio_bps_bounds(input_data.coverage_ratio_bps_bounds)?;
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ new value: {:?}",
+            tranche_config.get_coverage_ratio_bps_bounds()
+        );
+
+        emit!(CoverageRatioBpsBoundsUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.get_coverage_ratio_bps_bounds(),
+        });
+    }
+
+    // lifecycle
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::LIFECYCLE) {
+        msg!("update tranche_config lifecycle");
+
+        input_data
+            .lifecycle
+            .is_valid()
+            .ok_or(VyperErrorCode::InvalidLifecycle)?;
+
+        let old_value = tranche_config.lifecycle;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.lifecycle);
+
+        tranche_config.lifecycle = input_data.lifecycle;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.lifecycle);
+
+        emit!(LifecycleUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.lifecycle,
+        });
+    }
+
+    // reserve fair value stale seconds th
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::RESERVE_FAIR_VALUE_STALE_SECONDS_THRESHOLD)
+    {
+        msg!("update tranche_data reserve_fair_value stale_seconds_threshold");
+
+        let slot_tracking = &mut tranche_config.tranche_data.reserve_fair_value.slot_tracking;
+        let old_value = slot_tracking.stale_seconds_threshold;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", slot_tracking.stale_seconds_threshold);
+
+        slot_tracking.stale_seconds_threshold =
+            input_data.reserve_fair_value_stale_seconds_threshold;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", slot_tracking.stale_seconds_threshold);
+
+        emit!(ReserveFairValueStaleSecondsThresholdUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: slot_tracking.stale_seconds_threshold,
+        });
+    }
+
+    // tranche fair value stale seconds th
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::TRANCHE_FAIR_VALUE_STALE_SECONDS_THRESHOLD)
+    {
+        msg!("update tranche_data tranche_fair_value stale_seconds_threshold");
+
+        let slot_tracking = &mut tranche_config.tranche_data.tranche_fair_value.slot_tracking;
+        let old_value = slot_tracking.stale_seconds_threshold;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", slot_tracking.stale_seconds_threshold);
+
+        slot_tracking.stale_seconds_threshold =
+            input_data.tranche_fair_value_stale_seconds_threshold;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", slot_tracking.stale_seconds_threshold);
+
+        emit!(TrancheFairValueStaleSecondsThresholdUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: slot_tracking.stale_seconds_threshold,
+        });
+    }
+
+    // reserve fair value staleness mode
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::RESERVE_FAIR_VALUE_STALENESS_MODE) {
+        msg!("update tranche_data reserve_fair_value staleness_mode");
+
+        let slot_tracking = &mut tranche_config.tranche_data.reserve_fair_value.slot_tracking;
+        let old_value = slot_tracking.staleness_mode;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", slot_tracking.staleness_mode);
+
+        slot_tracking.staleness_mode = input_data.reserve_fair_value_staleness_mode;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", slot_tracking.staleness_mode);
+
+        emit!(ReserveFairValueStalenessModeUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: slot_tracking.staleness_mode,
+        });
+    }
+
+    // tranche fair value staleness mode
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::TRANCHE_FAIR_VALUE_STALENESS_MODE) {
+        msg!("update tranche_data tranche_fair_value staleness_mode");
+
+        let slot_tracking = &mut tranche_config.tranche_data.tranche_fair_value.slot_tracking;
+        let old_value = slot_tracking.staleness_mode;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", slot_tracking.staleness_mode);
+
+        slot_tracking.staleness_mode = input_data.tranche_fair_value_staleness_mode;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", slot_tracking.staleness_mode);
+
+        emit!(TrancheFairValueStalenessModeUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: slot_tracking.staleness_mode,
+        });
+    }
+
+    // orphaned quantity destination
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::ORPHANED_QUANTITY_DESTINATION) {
+        msg!("update tranche_config orphaned_quantity_destination");
+
+        let old_value = tranche_config.orphaned_quantity_destination;
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ old value: {:?}",
+            tranche_config.orphaned_quantity_destination
+        );
+
+        tranche_config.orphaned_quantity_destination = input_data.orphaned_quantity_destination;
+
+        #[cfg(feature = "debug")]
+        msg!(
+            "+ new value: {:?}",
+            tranche_config.orphaned_quantity_destination
+        );
+
+        emit!(OrphanedQuantityDestinationUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.orphaned_quantity_destination,
+        });
+    }
+
+    // min initial deposit
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::MIN_INITIAL_DEPOSIT) {
+        msg!("update tranche_config min_initial_deposit");
+
+        let old_value = tranche_config.min_initial_deposit;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {:?}", tranche_config.min_initial_deposit);
+
+        tranche_config.min_initial_deposit = input_data.min_initial_deposit;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {:?}", tranche_config.min_initial_deposit);
+
+        emit!(MinInitialDepositUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.min_initial_deposit,
+        });
+    }
+
+    // dead shares quantity
+
+    if update_bitmask.contains(UpdateTrancheConfigFlags::DEAD_SHARES_QUANTITY) {
+        msg!("update tranche_config dead_shares_quantity");
+
+        let old_value = tranche_config.dead_shares_quantity;
+
+        #[cfg(feature = "debug")]
+        msg!("+ old value: {}", tranche_config.dead_shares_quantity);
+
+        tranche_config.dead_shares_quantity = input_data.dead_shares_quantity;
+
+        #[cfg(feature = "debug")]
+        msg!("+ new value: {}", tranche_config.dead_shares_quantity);
+
+        emit!(DeadSharesQuantityUpdated {
+            tranche_config: tranche_config_key,
+            signer,
+            old_value,
+            new_value: tranche_config.dead_shares_quantity,
+        });
     }
 
     Ok(())
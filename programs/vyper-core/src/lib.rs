@@ -1,4 +1,5 @@
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
@@ -42,12 +43,18 @@ pub mod vyper_core {
     }
 
     #[log_wrap_ix()]
-    pub fn deposit(ctx: Context<DepositContext>, input_data: DepositInput) -> Result<()> {
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositContext<'info>>,
+        input_data: DepositInput,
+    ) -> Result<()> {
         instructions::deposit::handler(ctx, input_data)
     }
 
     #[log_wrap_ix()]
-    pub fn redeem(ctx: Context<RedeemContext>, input_data: RedeemInput) -> Result<()> {
+    pub fn redeem<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemContext<'info>>,
+        input_data: RedeemInput,
+    ) -> Result<()> {
         instructions::redeem::handler(ctx, input_data)
     }
 
@@ -55,4 +62,171 @@ pub mod vyper_core {
     pub fn collect_fee(ctx: Context<CollectFeeContext>) -> Result<()> {
         instructions::collect_fee::handler(ctx)
     }
+
+    #[log_wrap_ix()]
+    pub fn propose_owner(ctx: Context<ProposeOwnerContext>) -> Result<()> {
+        instructions::propose_owner::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn accept_owner(ctx: Context<AcceptOwnerContext>) -> Result<()> {
+        instructions::accept_owner::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn queue_plugin_update(ctx: Context<QueuePluginUpdateContext>) -> Result<()> {
+        instructions::queue_plugin_update::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn apply_plugin_update(ctx: Context<ApplyPluginUpdateContext>) -> Result<()> {
+        instructions::apply_plugin_update::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn cancel_plugin_update(ctx: Context<CancelPluginUpdateContext>) -> Result<()> {
+        instructions::cancel_plugin_update::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn queue_tranche_data_update(
+        ctx: Context<QueueTrancheDataUpdateContext>,
+        input_data: UpdateTrancheDataInput,
+    ) -> Result<()> {
+        instructions::queue_tranche_data_update::handler(ctx, input_data)
+    }
+
+    #[log_wrap_ix()]
+    pub fn apply_tranche_data_update(ctx: Context<ApplyTrancheDataUpdateContext>) -> Result<()> {
+        instructions::apply_tranche_data_update::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn cancel_tranche_data_update(ctx: Context<CancelTrancheDataUpdateContext>) -> Result<()> {
+        instructions::cancel_tranche_data_update::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn guardian_halt(ctx: Context<GuardianHaltContext>, halt_flags: u16) -> Result<()> {
+        instructions::guardian_halt::handler(ctx, halt_flags)
+    }
+
+    #[log_wrap_ix()]
+    pub fn close_tranche<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseTrancheContext<'info>>,
+    ) -> Result<()> {
+        instructions::close_tranche::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn init_fee_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitFeeSplitContext<'info>>,
+        input_data: InitFeeSplitInput,
+    ) -> Result<()> {
+        instructions::init_fee_split::handler(ctx, input_data)
+    }
+
+    #[log_wrap_ix()]
+    pub fn collect_fee_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectFeeSplitContext<'info>>,
+    ) -> Result<()> {
+        instructions::collect_fee_split::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn initialize_protocol_config(
+        ctx: Context<InitializeProtocolConfigContext>,
+        input_data: InitializeProtocolConfigInput,
+    ) -> Result<()> {
+        instructions::initialize_protocol_config::handler(ctx, input_data)
+    }
+
+    #[log_wrap_ix()]
+    pub fn update_protocol_config(
+        ctx: Context<UpdateProtocolConfigContext>,
+        input_data: UpdateProtocolConfigInput,
+    ) -> Result<()> {
+        instructions::update_protocol_config::handler(ctx, input_data)
+    }
+
+    #[log_wrap_ix()]
+    pub fn collect_protocol_fee(ctx: Context<CollectProtocolFeeContext>) -> Result<()> {
+        instructions::collect_protocol_fee::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn migrate(ctx: Context<MigrateContext>) -> Result<()> {
+        instructions::migrate::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn add_allowlist_entry(ctx: Context<AddAllowlistEntryContext>) -> Result<()> {
+        instructions::add_allowlist_entry::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn remove_allowlist_entry(ctx: Context<RemoveAllowlistEntryContext>) -> Result<()> {
+        instructions::remove_allowlist_entry::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn init_user_position(ctx: Context<InitUserPositionContext>) -> Result<()> {
+        instructions::init_user_position::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn set_user_deposit_cap(
+        ctx: Context<SetUserDepositCapContext>,
+        deposit_cap: Option<[Option<u64>; 2]>,
+    ) -> Result<()> {
+        instructions::set_user_deposit_cap::handler(ctx, deposit_cap)
+    }
+
+    #[log_wrap_ix()]
+    pub fn settle(ctx: Context<SettleContext>) -> Result<()> {
+        instructions::settle::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn queue_epoch_roll(
+        ctx: Context<QueueEpochRollContext>,
+        input_data: QueueEpochRollInput,
+    ) -> Result<()> {
+        instructions::queue_epoch_roll::handler(ctx, input_data)
+    }
+
+    #[log_wrap_ix()]
+    pub fn roll_epoch(ctx: Context<RollEpochContext>) -> Result<()> {
+        instructions::roll_epoch::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn cancel_epoch_roll(ctx: Context<CancelEpochRollContext>) -> Result<()> {
+        instructions::cancel_epoch_roll::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn create_queued_request<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateQueuedRequestContext<'info>>,
+        input_data: CreateQueuedRequestInput,
+    ) -> Result<()> {
+        instructions::create_queued_request::handler(ctx, input_data)
+    }
+
+    #[log_wrap_ix()]
+    pub fn process_queued_request<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProcessQueuedRequestContext<'info>>,
+    ) -> Result<()> {
+        instructions::process_queued_request::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn claim_queued_request(ctx: Context<ClaimQueuedRequestContext>) -> Result<()> {
+        instructions::claim_queued_request::handler(ctx)
+    }
+
+    #[log_wrap_ix()]
+    pub fn init_dead_shares(ctx: Context<InitDeadSharesContext>) -> Result<()> {
+        instructions::init_dead_shares::handler(ctx)
+    }
 }
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+/// Allowlist entry for a user of a permissioned tranche, created by the owner or the allowlist admin
+#[account]
+pub struct AllowlistEntry {
+    /// Tranche config the entry belongs to
+    pub tranche_config: Pubkey,
+
+    /// Allowlisted user
+    pub user: Pubkey,
+
+    /// Creation date
+    pub created_at: i64,
+}
+
+impl AllowlistEntry {
+    /// Check if the given accounts contain a valid allowlist entry for the user
+    pub fn is_allowlisted(accounts: &[AccountInfo], tranche_config: Pubkey, user: Pubkey) -> bool {
+        accounts
+            .iter()
+            .filter(|account| *account.owner == crate::ID)
+            .filter_map(|account| Account::<AllowlistEntry>::try_from(account).ok())
+            .any(|entry| entry.tranche_config == tranche_config && entry.user == user)
+    }
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        32 + // pub user: Pubkey,
+        8; // pub created_at: i64,
+}
@@ -0,0 +1,13 @@
+bitflags::bitflags! {
+    pub struct AllowlistRestrictedIxFlags: u16 {
+        /// Allowlist restricted: Deposits
+        const DEPOSITS = 1 << 0;
+
+        /// Allowlist restricted: Redeems
+        const REDEEMS = 1 << 1;
+
+        /// Restrict all operations
+        const ALL = Self::DEPOSITS.bits
+                       | Self::REDEEMS.bits;
+    }
+}
@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+/// Unit in which the deposit caps are expressed
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepositCapMode {
+    /// Caps compared with the raw reserve quantity
+    Quantity,
+
+    /// Caps compared with the reserve value, quantity times the reserve fair value
+    ReserveValue,
+}
+
+impl Default for DepositCapMode {
+    fn default() -> Self {
+        DepositCapMode::Quantity
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+/// Settled values of a closed epoch, one account per epoch
+#[account]
+pub struct EpochRecord {
+    /// Tranche config the epoch belongs to
+    pub tranche_config: Pubkey,
+
+    /// Epoch number, starting from zero
+    pub epoch: u64,
+
+    /// Redeem logic state used during the epoch
+    pub redeem_logic_program_state: Pubkey,
+
+    /// Settlement reserve fair value
+    pub reserve_fair_value: [[u8; 16]; 10],
+
+    /// Settlement tranche fair value for senior and junior side
+    pub tranche_fair_value: [[u8; 16]; 2],
+
+    /// Deposited quantity at settlement, carried over to the next epoch
+    pub deposited_quantity: [u64; 2],
+
+    /// Roll date
+    pub rolled_at: i64,
+}
+
+impl EpochRecord {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        8 + // pub epoch: u64,
+        32 + // pub redeem_logic_program_state: Pubkey,
+        16 * 10 + // pub reserve_fair_value: [[u8; 16]; 10],
+        16 * 2 + // pub tranche_fair_value: [[u8; 16]; 2],
+        2 * 8 + // pub deposited_quantity: [u64; 2],
+        8; // pub rolled_at: i64,
+}
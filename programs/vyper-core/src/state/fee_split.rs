@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+/// Token account receiving a share of the collected fees
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Default)]
+pub struct FeeRecipient {
+    /// Reserve token account of the recipient
+    pub token_account: Pubkey,
+
+    /// Share of the collected fees expressed in basis points
+    pub weight_bps: u16,
+}
+
+impl FeeRecipient {
+    pub const LEN: usize = 32 + // pub token_account: Pubkey,
+    2; // pub weight_bps: u16,
+}
+
+/// Fee recipients of a tranche config, fixed once created
+#[account]
+pub struct FeeSplit {
+    /// Tranche config whose fees are split
+    pub tranche_config: Pubkey,
+
+    /// Fee recipients, weights sum up to 10_000 bps
+    pub recipients: Vec<FeeRecipient>,
+}
+
+impl FeeSplit {
+    pub const MAX_RECIPIENTS: usize = 5;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        4 + FeeSplit::MAX_RECIPIENTS * FeeRecipient::LEN; // pub recipients: Vec<FeeRecipient>,
+}
@@ -8,15 +8,15 @@ use crate::errors::VyperErrorCode;
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Default)]
 pub struct LastUpdate {
     slot: u64,
-    _padding: [u8; 8],
+    unix_timestamp: i64,
 }
 
 impl LastUpdate {
     /// Create new last update
-    pub fn new(slot: u64) -> Self {
+    pub fn new(slot: u64, unix_timestamp: i64) -> Self {
         Self {
             slot,
-            _padding: [0_u8; 8],
+            unix_timestamp,
         }
     }
 
@@ -26,16 +26,31 @@ impl LastUpdate {
             .ok_or_else(|| VyperErrorCode::MathError.into())
     }
 
+    /// Return seconds elapsed since given unix timestamp, a timestamp in the future counts as zero
+    pub fn seconds_elapsed(&self, unix_timestamp: i64) -> u64 {
+        unix_timestamp.saturating_sub(self.unix_timestamp).max(0) as u64
+    }
+
     /// Set last update slot
     pub fn get_slot(&self) -> u64 {
         self.slot
     }
 
+    /// Get last update unix timestamp
+    pub fn get_unix_timestamp(&self) -> i64 {
+        self.unix_timestamp
+    }
+
     /// Set last update slot
     pub fn update_slot(&mut self, slot: u64) {
         self.slot = slot;
     }
 
+    /// Set last update unix timestamp
+    pub fn update_unix_timestamp(&mut self, unix_timestamp: i64) {
+        self.unix_timestamp = unix_timestamp;
+    }
+
     pub const LEN: usize = 8 + // slot: u64,
-        8; // _padding: [u8; 8],
+        8; // unix_timestamp: i64,
 }
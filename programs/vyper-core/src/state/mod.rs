@@ -21,3 +21,48 @@ pub use slot_tracking::*;
 
 pub mod last_update;
 pub use last_update::*;
+
+pub mod pending_plugin_update;
+pub use pending_plugin_update::*;
+
+pub mod pending_tranche_data_update;
+pub use pending_tranche_data_update::*;
+
+pub mod reserve_surplus_destination;
+pub use reserve_surplus_destination::*;
+
+pub mod fee_split;
+pub use fee_split::*;
+
+pub mod protocol_config;
+pub use protocol_config::*;
+
+pub mod allowlist_restricted_ix_flags;
+pub use allowlist_restricted_ix_flags::*;
+
+pub mod allowlist_entry;
+pub use allowlist_entry::*;
+
+pub mod user_position;
+pub use user_position::*;
+
+pub mod deposit_cap_mode;
+pub use deposit_cap_mode::*;
+
+pub mod tranche_lifecycle;
+pub use tranche_lifecycle::*;
+
+pub mod epoch_record;
+pub use epoch_record::*;
+
+pub mod queued_request;
+pub use queued_request::*;
+
+pub mod staleness_mode;
+pub use staleness_mode::*;
+
+pub mod orphaned_quantity_destination;
+pub use orphaned_quantity_destination::*;
+
+pub mod pending_epoch_roll;
+pub use pending_epoch_roll::*;
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+/// Where the tranche fair value refresh moves the deposited quantity of a side with zero tranche supply,
+/// the quantity is not owned by any holder and would be captured by the next depositor.
+/// Tranche stays the default so existing tranches keep their behavior, it's also the value read from
+/// accounts migrated from versions without this field. Owners opt in to another destination
+/// with update_tranche_data
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrphanedQuantityDestination {
+    /// Leave the quantity on the side and the fair value unchanged
+    Tranche,
+
+    /// Add the quantity to the fee to collect
+    Fee,
+
+    /// Add the quantity to the other side deposited quantity, if it has a tranche supply
+    OtherTranche,
+
+    /// Remove the quantity from the deposited quantity, it's then reconciled as reserve surplus
+    Untracked,
+
+    /// Remove the quantity like Untracked and reset the side fair value to one,
+    /// the next depositor enters the side as its first depositor without any windfall
+    ResetFairValue,
+}
+
+impl Default for OrphanedQuantityDestination {
+    fn default() -> Self {
+        OrphanedQuantityDestination::Tranche
+    }
+}
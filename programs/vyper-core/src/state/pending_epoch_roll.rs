@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use super::TrancheLifecycle;
+
+/// Next epoch parameters queued by the tranche config owner, the epoch can be rolled only after the timelock expires
+#[account]
+pub struct PendingEpochRoll {
+    /// Tranche config to roll
+    pub tranche_config: Pubkey,
+
+    /// Redeem logic state of the next epoch
+    pub redeem_logic_program_state: Pubkey,
+
+    /// Subscription window and maturity of the next epoch
+    pub lifecycle: TrancheLifecycle,
+
+    /// Slot when the roll has been queued
+    pub queued_slot: u64,
+
+    /// First slot when the epoch can be rolled
+    pub executable_slot: u64,
+}
+
+impl PendingEpochRoll {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        32 + // pub redeem_logic_program_state: Pubkey,
+        TrancheLifecycle::LEN + // pub lifecycle: TrancheLifecycle,
+        8 + // pub queued_slot: u64,
+        8; // pub executable_slot: u64,
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+/// Plugin swap queued by the tranche config owner, it can be applied only after the timelock expires
+#[account]
+pub struct PendingPluginUpdate {
+    /// Tranche config where the plugins will be replaced
+    pub tranche_config: Pubkey,
+
+    pub rate_program: Pubkey,
+    pub rate_program_state: Pubkey,
+
+    pub redeem_logic_program: Pubkey,
+    pub redeem_logic_program_state: Pubkey,
+
+    /// Slot when the update has been queued
+    pub queued_slot: u64,
+
+    /// First slot when the update can be applied
+    pub executable_slot: u64,
+}
+
+impl PendingPluginUpdate {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        32 + // pub rate_program: Pubkey,
+        32 + // pub rate_program_state: Pubkey,
+        32 + // pub redeem_logic_program: Pubkey,
+        32 + // pub redeem_logic_program_state: Pubkey,
+        8 + // pub queued_slot: u64,
+        8; // pub executable_slot: u64,
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::UpdateTrancheDataInput;
+
+/// Tranche data update queued by the tranche config owner, it can be applied only after the timelock expires
+#[account]
+pub struct PendingTrancheDataUpdate {
+    /// Tranche config where the update will be applied
+    pub tranche_config: Pubkey,
+
+    /// Queued update, same payload of update_tranche_data
+    pub update: UpdateTrancheDataInput,
+
+    /// Slot when the update has been queued
+    pub queued_slot: u64,
+
+    /// First slot when the update can be applied
+    pub executable_slot: u64,
+}
+
+impl PendingTrancheDataUpdate {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        UpdateTrancheDataInput::LEN + // pub update: UpdateTrancheDataInput,
+        8 + // pub queued_slot: u64,
+        8; // pub executable_slot: u64,
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VyperErrorCode;
+
+use super::TrancheHaltFlags;
+
+/// Protocol wide configuration, a single PDA for the whole program
+#[account]
+pub struct ProtocolConfig {
+    /// Account which is allowed to update the protocol config
+    pub admin: Pubkey,
+
+    /// Share of the tranche fees retained by the protocol, expressed in basis points
+    pub protocol_fee_bps: u16,
+
+    /// Owner of the token accounts receiving the protocol fees
+    pub treasury: Pubkey,
+
+    /// halt flags applied to every tranche, on top of the tranche ones
+    halt_flags: u16,
+
+    /// Reserved space for future upgrades
+    _reserved: [u8; 128],
+}
+
+impl ProtocolConfig {
+    pub fn get_halt_flags(&self) -> Result<TrancheHaltFlags> {
+        TrancheHaltFlags::from_bits(self.halt_flags)
+            .ok_or_else(|| VyperErrorCode::InvalidTrancheHaltFlags.into())
+    }
+
+    pub fn set_halt_flags(&mut self, bits: u16) -> Result<()> {
+        TrancheHaltFlags::from_bits(bits).ok_or(VyperErrorCode::InvalidTrancheHaltFlags)?;
+        self.halt_flags = bits;
+        Ok(())
+    }
+
+    pub fn set_protocol_fee_bps(&mut self, protocol_fee_bps: u16) -> Result<()> {
+        if protocol_fee_bps > 10_000 {
+            msg!("protocol fee can't exceed 10_000 bps");
+            return err!(VyperErrorCode::InvalidInput);
+        }
+        self.protocol_fee_bps = protocol_fee_bps;
+        Ok(())
+    }
+
+    /// Protocol share of the given fee quantity
+    pub fn get_protocol_fee(&self, fee_quantity: u64) -> Result<u64> {
+        u64::try_from(
+            (fee_quantity as u128)
+                .checked_mul(self.protocol_fee_bps as u128)
+                .ok_or(VyperErrorCode::MathError)?
+                / 10_000,
+        )
+        .map_err(|_| VyperErrorCode::MathError.into())
+    }
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub admin: Pubkey,
+        2 + // pub protocol_fee_bps: u16,
+        32 + // pub treasury: Pubkey,
+        2 + // halt_flags: u16,
+        128; // _reserved: [u8; 128],
+}
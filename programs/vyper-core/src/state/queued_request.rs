@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+/// Action requested by a queued request
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueuedRequestKind {
+    /// Reserve locked to mint senior and junior tranches
+    Deposit,
+
+    /// Senior and junior tranches locked to receive reserve
+    Redeem,
+}
+
+impl Default for QueuedRequestKind {
+    fn default() -> Self {
+        QueuedRequestKind::Deposit
+    }
+}
+
+/// Deposit or redeem executed at the first tranche fair value refreshed after the request,
+/// one pending request per wallet and tranche config.
+/// The request can be cancelled only until that refresh
+#[account]
+pub struct QueuedRequest {
+    /// Tranche config the request belongs to
+    pub tranche_config: Pubkey,
+
+    /// Wallet owning the request
+    pub user: Pubkey,
+
+    pub kind: QueuedRequestKind,
+
+    /// Locked quantity for senior and junior side, reserve for deposits and tranches for redeems
+    pub quantity: [u64; 2],
+
+    /// Slot when the request has been created
+    pub requested_slot: u64,
+
+    /// Request executed, the outputs can be claimed
+    pub processed: bool,
+
+    /// Reserve to claim for processed redeems net of exit fees, or the locked reserve of a rejected deposit
+    pub reserve_to_claim_quantity: u64,
+
+    /// Minimum senior and junior tranches minted by a deposit, the request is rejected below it
+    pub min_tranche_out: [Option<u64>; 2],
+
+    /// Minimum reserve received by a redeem net of exit fees, the request is rejected below it
+    pub min_reserve_out: Option<u64>,
+}
+
+impl QueuedRequest {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        32 + // pub user: Pubkey,
+        1 + // pub kind: QueuedRequestKind,
+        2 * 8 + // pub quantity: [u64; 2],
+        8 + // pub requested_slot: u64,
+        1 + // pub processed: bool,
+        8 + // pub reserve_to_claim_quantity: u64,
+        2 * (1 + 8) + // pub min_tranche_out: [Option<u64>; 2],
+        1 + 8; // pub min_reserve_out: Option<u64>,
+}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+/// Where collect_fee moves the reserve tokens not tracked by deposited quantities or fees
+/// (rounding dust and tokens sent straight to the reserve vault)
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveSurplusDestination {
+    /// Leave the surplus untracked in the reserve vault
+    Untracked,
+
+    /// Add the surplus to the fee to collect
+    Fee,
+
+    /// Add the surplus to the senior deposited quantity
+    SeniorTranche,
+
+    /// Add the surplus to the junior deposited quantity
+    JuniorTranche,
+}
+
+impl Default for ReserveSurplusDestination {
+    fn default() -> Self {
+        ReserveSurplusDestination::Untracked
+    }
+}
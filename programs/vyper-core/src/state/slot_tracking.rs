@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 
 use crate::errors::VyperErrorCode;
 
-use super::LastUpdate;
+use super::{LastUpdate, StalenessMode};
 
 /// Tracking of slot information
 #[repr(C, align(8))]
@@ -12,18 +12,27 @@ pub struct SlotTracking {
 
     /// threshold for defining a slot tracked value stale
     pub stale_slot_threshold: u64,
+
+    /// threshold in seconds for defining a slot tracked value stale
+    pub stale_seconds_threshold: u64,
+
+    /// thresholds checked for defining a slot tracked value stale
+    pub staleness_mode: StalenessMode,
 }
 
 impl SlotTracking {
-    pub fn new(slot: u64) -> Self {
+    pub fn new(slot: u64, unix_timestamp: i64) -> Self {
         Self {
-            last_update: LastUpdate::new(slot),
+            last_update: LastUpdate::new(slot, unix_timestamp),
             stale_slot_threshold: 2,
+            stale_seconds_threshold: 0,
+            staleness_mode: StalenessMode::Slots,
         }
     }
 
-    pub fn update(&mut self, slot: u64) {
+    pub fn update(&mut self, slot: u64, unix_timestamp: i64) {
         self.last_update.update_slot(slot);
+        self.last_update.update_unix_timestamp(unix_timestamp);
     }
 
     pub fn slot_elapsed(&self, current_slot: u64) -> Result<u64> {
@@ -32,14 +41,41 @@ impl SlotTracking {
             .ok_or_else(|| VyperErrorCode::MathError.into())
     }
 
-    pub fn is_stale(&self, current_slot: u64) -> Result<bool> {
-        Ok(self.slot_elapsed(current_slot)? >= self.stale_slot_threshold)
+    pub fn seconds_elapsed(&self, current_unix_timestamp: i64) -> u64 {
+        self.last_update.seconds_elapsed(current_unix_timestamp)
+    }
+
+    pub fn is_stale(&self, current_slot: u64, current_unix_timestamp: i64) -> Result<bool> {
+        self.is_stale_since(
+            self.last_update,
+            LastUpdate::new(current_slot, current_unix_timestamp),
+        )
+    }
+
+    /// Check a value last updated at the given slot and timestamp against the thresholds
+    pub fn is_stale_since(&self, last_update: LastUpdate, current: LastUpdate) -> Result<bool> {
+        let is_slot_stale =
+            last_update.slots_elapsed(current.get_slot())? >= self.stale_slot_threshold;
+        let is_seconds_stale = last_update.seconds_elapsed(current.get_unix_timestamp())
+            >= self.stale_seconds_threshold;
+
+        Ok(match self.staleness_mode {
+            StalenessMode::Slots => is_slot_stale,
+            StalenessMode::Seconds => is_seconds_stale,
+            StalenessMode::SlotsOrSeconds => is_slot_stale || is_seconds_stale,
+        })
     }
 
     pub fn get_last_update_slot(&self) -> u64 {
         self.last_update.get_slot()
     }
 
+    pub fn get_last_update_unix_timestamp(&self) -> i64 {
+        self.last_update.get_unix_timestamp()
+    }
+
     pub const LEN: usize = LastUpdate::LEN + // last_update: LastUpdate,
-    8; // pub stale_slot_threshold: u64,
+    8 + // pub stale_slot_threshold: u64,
+    8 + // pub stale_seconds_threshold: u64,
+    1; // pub staleness_mode: StalenessMode,
 }
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+/// Clock used to define a slot tracked value stale
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StalenessMode {
+    /// Stale once the stale slot threshold is reached
+    Slots,
+
+    /// Stale once the stale seconds threshold is reached
+    Seconds,
+
+    /// Stale once either the stale slot or the stale seconds threshold is reached
+    SlotsOrSeconds,
+}
+
+impl Default for StalenessMode {
+    fn default() -> Self {
+        StalenessMode::Slots
+    }
+}
@@ -1,6 +1,15 @@
+use std::cmp::Ordering;
+
 use anchor_lang::prelude::*;
+use boolinator::Boolinator;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+use crate::errors::VyperErrorCode;
 
-use super::TrancheData;
+use super::{
+    AllowlistRestrictedIxFlags, DepositCapMode, OrphanedQuantityDestination, ProtocolConfig,
+    ReserveSurplusDestination, TrancheData, TrancheLifecycle, UserPosition,
+};
 
 #[repr(C, align(8))]
 #[account]
@@ -38,11 +47,238 @@ pub struct TrancheConfig {
     /// Creation date
     pub created_at: i64,
 
-    /// Reserved space for future upgrades
-    _reserved: [u8; 256],
+    /// Account proposed as new owner, it needs to accept the ownership before becoming effective
+    pub pending_owner: Option<Pubkey>,
+
+    /// Number of slots a queued plugin update, loosening tranche data update or epoch roll has to wait before being applied
+    pub timelock_slots: u64,
+
+    /// Account allowed to add halt flags, it can't remove them
+    pub guardian: Option<Pubkey>,
+
+    /// Destination of the reserve surplus reconciled during fee collection
+    pub reserve_surplus_destination: ReserveSurplusDestination,
+
+    /// Fees are distributed through the fee split account, collect_fee is disabled
+    pub has_fee_split: bool,
+
+    /// Fees retained by the protocol, waiting to be sent to the protocol treasury
+    pub protocol_fee_to_collect_quantity: u64,
+
+    /// Account allowed to manage the allowlist together with the owner
+    pub allowlist_admin: Option<Pubkey>,
+
+    /// flags for instructions restricted to allowlisted users
+    allowlist_restricted_ixs: u16,
+
+    /// default deposit cap of a single wallet for senior side and junior side, if the value is None the cap is disabled
+    pub wallet_deposit_cap: [Option<u64>; 2],
+
+    /// unit of the deposit caps, raw reserve quantity or reserve value
+    pub deposit_cap_mode: DepositCapMode,
+
+    /// deposit cap on senior side plus junior side, if the value is None the cap is disabled
+    pub total_deposit_cap: Option<u64>,
+
+    /// min and max ratio between senior and junior deposited quantity in bps, if the value is None the bound is disabled
+    coverage_ratio_bps_bounds: [Option<u64>; 2],
+
+    /// subscription window and maturity of the tranche
+    pub lifecycle: TrancheLifecycle,
+
+    /// fair values frozen by the final settlement after maturity, refreshes are rejected
+    pub settled: bool,
+
+    /// current epoch, incremented at each roll
+    pub epoch: u64,
+
+    /// reserve locked by queued deposits or owed to processed queued redeems, not tracked by the tranche data
+    pub queued_reserve_quantity: u64,
+
+    /// Destination of the deposited quantity of a side with zero tranche supply, applied on refresh
+    pub orphaned_quantity_destination: OrphanedQuantityDestination,
+
+    /// minimum net reserve quantity of the first deposit of senior side and junior side, when the tranche supply is empty
+    pub min_initial_deposit: [u64; 2],
+
+    /// tranches locked by the first deposit of a side, minted to the tranche authority out of the depositor tranches
+    pub dead_shares_quantity: u64,
+
+    /// decimals of the reserve mint, fair values are expressed in whole reserve tokens per whole tranche token
+    pub reserve_mint_decimals: u8,
+
+    /// decimals of the senior and junior tranche mints
+    pub tranche_mint_decimals: u8,
+
+    /// a user position deposit cap override has been set, deposits require the user position even without a default wallet cap
+    pub has_user_deposit_cap_override: bool,
 }
 
 impl TrancheConfig {
+    /// Oldest account layout accepted by the instructions, older accounts need to be migrated
+    pub const MIN_SUPPORTED_VERSION: [u8; 3] = [0, 4, 0];
+
+    pub fn is_supported_version(&self) -> bool {
+        self.version >= Self::MIN_SUPPORTED_VERSION
+    }
+
+    pub fn get_allowlist_restricted_ixs(&self) -> Result<AllowlistRestrictedIxFlags> {
+        AllowlistRestrictedIxFlags::from_bits(self.allowlist_restricted_ixs)
+            .ok_or_else(|| VyperErrorCode::InvalidAllowlistRestrictedIxFlags.into())
+    }
+
+    pub fn set_allowlist_restricted_ixs(&mut self, bits: u16) -> Result<()> {
+        AllowlistRestrictedIxFlags::from_bits(bits)
+            .ok_or(VyperErrorCode::InvalidAllowlistRestrictedIxFlags)?;
+        self.allowlist_restricted_ixs = bits;
+        Ok(())
+    }
+
+    pub fn get_coverage_ratio_bps_bounds(&self) -> [Option<u64>; 2] {
+        self.coverage_ratio_bps_bounds
+    }
+
+    pub fn set_coverage_ratio_bps_bounds(&mut self, bounds: [Option<u64>; 2]) -> Result<()> {
+        if let [Some(min_ratio_bps), Some(max_ratio_bps)] = bounds {
+            (min_ratio_bps <= max_ratio_bps).ok_or(VyperErrorCode::InvalidCoverageRatioBounds)?;
+        }
+        self.coverage_ratio_bps_bounds = bounds;
+        Ok(())
+    }
+
+    /// Position of the ratio between senior and junior deposited quantity relative to the bounds,
+    /// Less below the minimum, Greater above the maximum, Equal within the bounds or for an empty pool
+    fn get_coverage_ratio_position(&self, deposited_quantity: [u64; 2]) -> Ordering {
+        let [senior_qty, junior_qty] = deposited_quantity;
+        if senior_qty == 0 && junior_qty == 0 {
+            return Ordering::Equal;
+        }
+
+        // ratio compared as senior * 10_000 against bound * junior to avoid the division
+        let scaled_senior_qty = senior_qty as u128 * 10_000;
+        let [min_ratio_bps, max_ratio_bps] = self.coverage_ratio_bps_bounds;
+
+        if let Some(min_ratio_bps) = min_ratio_bps {
+            if scaled_senior_qty < min_ratio_bps as u128 * junior_qty as u128 {
+                return Ordering::Less;
+            }
+        }
+
+        if let Some(max_ratio_bps) = max_ratio_bps {
+            if scaled_senior_qty > max_ratio_bps as u128 * junior_qty as u128 {
+                return Ordering::Greater;
+            }
+        }
+
+        Ordering::Equal
+    }
+
+    /// Check that an action moving the deposited quantities from old to new keeps the senior junior ratio within the bounds.
+    /// A ratio already out of the bounds can't be moved further away from them, any move towards them is accepted
+    pub fn check_coverage_ratio(
+        &self,
+        old_deposited_quantity: [u64; 2],
+        new_deposited_quantity: [u64; 2],
+    ) -> Result<()> {
+        let new_position = self.get_coverage_ratio_position(new_deposited_quantity);
+        if new_position == Ordering::Equal {
+            return Ok(());
+        }
+
+        // new ratio compared to the old one as new_senior * old_junior against old_senior * new_junior
+        let [old_senior_qty, old_junior_qty] = old_deposited_quantity;
+        let [new_senior_qty, new_junior_qty] = new_deposited_quantity;
+        let ratio_move = (new_senior_qty as u128 * old_junior_qty as u128)
+            .cmp(&(old_senior_qty as u128 * new_junior_qty as u128));
+        if self.get_coverage_ratio_position(old_deposited_quantity) == new_position
+            && ratio_move != new_position
+        {
+            return Ok(());
+        }
+
+        let [min_ratio_bps, max_ratio_bps] = self.coverage_ratio_bps_bounds;
+        match new_position {
+            Ordering::Less => msg!("coverage ratio below the minimum {:?} bps", min_ratio_bps),
+            _ => msg!("coverage ratio above the maximum {:?} bps", max_ratio_bps),
+        }
+        err!(VyperErrorCode::CoverageRatioOutOfBounds)
+    }
+
+    /// Check if a redeem leaves only the dead shares on one of the redeemed sides,
+    /// the last holder of a side can always exit regardless of the coverage ratio
+    pub fn is_last_holder_exit(
+        &self,
+        tranche_supply: [u64; 2],
+        redeemed_tranche_quantity: [u64; 2],
+    ) -> bool {
+        tranche_supply
+            .iter()
+            .zip(redeemed_tranche_quantity.iter())
+            .any(|(&supply, &redeemed_qty)| {
+                redeemed_qty > 0 && supply.saturating_sub(redeemed_qty) <= self.dead_shares_quantity
+            })
+    }
+
+    /// Check if deposits are tracked per wallet, either with the default cap or with user position overrides.
+    /// When true the user position is required
+    pub fn has_wallet_deposit_cap(&self) -> bool {
+        self.wallet_deposit_cap.iter().any(Option::is_some) || self.has_user_deposit_cap_override
+    }
+
+    /// Deposit cap of the given user position, the position override takes precedence over the default
+    pub fn get_wallet_deposit_cap(&self, user_position: &UserPosition) -> [Option<u64>; 2] {
+        user_position
+            .deposit_cap_override
+            .unwrap_or(self.wallet_deposit_cap)
+    }
+
+    /// Convert a reserve quantity in the unit of the deposit caps
+    pub fn get_deposit_cap_amount(&self, reserve_quantity: u64) -> Result<u64> {
+        match self.deposit_cap_mode {
+            DepositCapMode::Quantity => Ok(reserve_quantity),
+            DepositCapMode::ReserveValue => {
+                let reserve_fv =
+                    Decimal::deserialize(self.tranche_data.reserve_fair_value.value[0]);
+                (Decimal::from(reserve_quantity) * reserve_fv)
+                    .floor()
+                    .to_u64()
+                    .ok_or_else(|| VyperErrorCode::MathError.into())
+            }
+        }
+    }
+
+    /// Check that depositing the given quantities keeps the deposits within the side and total caps,
+    /// caps are expressed in reserve quantity or reserve value
+    pub fn check_deposit_cap(&self, net_reserve_quantity: [u64; 2]) -> Result<()> {
+        let mut total_deposit_cap_amount = 0u64;
+        for (i, &net_reserve_qty) in net_reserve_quantity.iter().enumerate() {
+            let deposit_cap_amount = self.get_deposit_cap_amount(
+                self.tranche_data.deposited_quantity[i]
+                    .checked_add(net_reserve_qty)
+                    .ok_or(VyperErrorCode::MathError)?,
+            )?;
+            if let Some(deposit_cap) = self.tranche_data.deposit_cap[i] {
+                if deposit_cap_amount > deposit_cap {
+                    return err!(VyperErrorCode::DepositExceededCap);
+                }
+            }
+            total_deposit_cap_amount = total_deposit_cap_amount
+                .checked_add(deposit_cap_amount)
+                .ok_or(VyperErrorCode::MathError)?;
+        }
+        if let Some(total_deposit_cap) = self.total_deposit_cap {
+            if total_deposit_cap_amount > total_deposit_cap {
+                return err!(VyperErrorCode::TotalDepositExceededCap);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if the account can manage the allowlist
+    pub fn is_allowlist_authority(&self, authority: Pubkey) -> bool {
+        self.owner == authority || self.allowlist_admin == Some(authority)
+    }
+
     pub fn authority_seeds(&self) -> [&[u8]; 3] {
         [
             self.authority_seed.as_ref(),
@@ -51,6 +287,132 @@ impl TrancheConfig {
         ]
     }
 
+    /// Move the reserve quantity not tracked by deposited quantities or fees to the configured destination,
+    /// returns the reconciled surplus
+    pub fn reconcile_reserve_surplus(&mut self, reserve_amount: u64) -> Result<u64> {
+        let reserve_surplus = self.tranche_data.get_reserve_surplus(
+            reserve_amount
+                .saturating_sub(self.protocol_fee_to_collect_quantity)
+                .saturating_sub(self.queued_reserve_quantity),
+        )?;
+        let tranche_data = &mut self.tranche_data;
+        match self.reserve_surplus_destination {
+            ReserveSurplusDestination::Untracked => {}
+            ReserveSurplusDestination::Fee => {
+                tranche_data.fee_to_collect_quantity = tranche_data
+                    .fee_to_collect_quantity
+                    .checked_add(reserve_surplus)
+                    .ok_or(VyperErrorCode::MathError)?;
+            }
+            ReserveSurplusDestination::SeniorTranche => {
+                tranche_data.deposited_quantity[0] = tranche_data.deposited_quantity[0]
+                    .checked_add(reserve_surplus)
+                    .ok_or(VyperErrorCode::MathError)?;
+            }
+            ReserveSurplusDestination::JuniorTranche => {
+                tranche_data.deposited_quantity[1] = tranche_data.deposited_quantity[1]
+                    .checked_add(reserve_surplus)
+                    .ok_or(VyperErrorCode::MathError)?;
+            }
+        }
+
+        Ok(reserve_surplus)
+    }
+
+    /// Move the deposited quantity of the sides with zero tranche supply to the configured destination,
+    /// returns the moved quantities
+    pub fn route_orphaned_quantity(
+        &mut self,
+        tranche_supply: [u64; 2],
+        protocol_config: &ProtocolConfig,
+    ) -> Result<[u64; 2]> {
+        let mut orphaned_quantity: [u64; 2] = [0; 2];
+        for i in 0..tranche_supply.len() {
+            let quantity = self.tranche_data.deposited_quantity[i];
+            if tranche_supply[i] > 0 || quantity == 0 {
+                continue;
+            }
+
+            let other = 1 - i;
+            match self.orphaned_quantity_destination {
+                OrphanedQuantityDestination::Tranche => continue,
+                OrphanedQuantityDestination::Fee => self.add_fee(quantity, protocol_config)?,
+                OrphanedQuantityDestination::OtherTranche => {
+                    if tranche_supply[other] == 0 {
+                        continue;
+                    }
+                    self.tranche_data.deposited_quantity[other] =
+                        self.tranche_data.deposited_quantity[other]
+                            .checked_add(quantity)
+                            .ok_or(VyperErrorCode::MathError)?;
+                }
+                OrphanedQuantityDestination::Untracked => {}
+                OrphanedQuantityDestination::ResetFairValue => {
+                    self.tranche_data.tranche_fair_value.value[i] = Decimal::ONE.serialize();
+                }
+            }
+            self.tranche_data.deposited_quantity[i] = 0;
+            orphaned_quantity[i] = quantity;
+        }
+
+        Ok(orphaned_quantity)
+    }
+
+    /// Check the minimum initial deposit of a side with an empty tranche supply,
+    /// returns the dead shares locked by the deposit
+    pub fn get_initial_dead_shares(
+        &self,
+        side: usize,
+        net_reserve_quantity: u64,
+        tranche_supply: u64,
+    ) -> Result<u64> {
+        if tranche_supply > 0 || net_reserve_quantity == 0 {
+            return Ok(0);
+        }
+
+        (net_reserve_quantity >= self.min_initial_deposit[side])
+            .ok_or(VyperErrorCode::MinInitialDepositNotReached)?;
+
+        Ok(self.dead_shares_quantity)
+    }
+
+    /// Reserve raw units worth one tranche raw unit at a fair value of one,
+    /// 10^(reserve_mint_decimals - tranche_mint_decimals)
+    pub fn get_decimals_scale(&self) -> Result<Decimal> {
+        if self.reserve_mint_decimals >= self.tranche_mint_decimals {
+            let scale = 10u64
+                .checked_pow((self.reserve_mint_decimals - self.tranche_mint_decimals) as u32)
+                .ok_or(VyperErrorCode::MathError)?;
+            Ok(Decimal::from(scale))
+        } else {
+            let scale = Decimal::try_new(
+                1,
+                (self.tranche_mint_decimals - self.reserve_mint_decimals) as u32,
+            )
+            .map_err(|_| VyperErrorCode::MathError)?;
+            Ok(scale)
+        }
+    }
+
+    /// Split the given fee between the tranche owner and the protocol
+    pub fn add_fee(&mut self, fee_quantity: u64, protocol_config: &ProtocolConfig) -> Result<()> {
+        let protocol_fee_quantity = protocol_config.get_protocol_fee(fee_quantity)?;
+        self.protocol_fee_to_collect_quantity = self
+            .protocol_fee_to_collect_quantity
+            .checked_add(protocol_fee_quantity)
+            .ok_or(VyperErrorCode::MathError)?;
+        self.tranche_data.fee_to_collect_quantity = self
+            .tranche_data
+            .fee_to_collect_quantity
+            .checked_add(
+                fee_quantity
+                    .checked_sub(protocol_fee_quantity)
+                    .ok_or(VyperErrorCode::MathError)?,
+            )
+            .ok_or(VyperErrorCode::MathError)?;
+        Ok(())
+    }
+
     pub const LEN: usize = 8 + // discriminator
         32 + // pub reserve_mint: Pubkey,
         32 + // pub reserve: Pubkey,
@@ -67,5 +429,26 @@ impl TrancheConfig {
         32 + // pub redeem_logic_program_state: Pubkey,
         3 + // pub version: [u8; 3],
         8 + // pub created_at: i64;
-        256; // _reserved: [u8; 256],
+        1 + 32 + // pub pending_owner: Option<Pubkey>,
+        8 + // pub timelock_slots: u64,
+        1 + 32 + // pub guardian: Option<Pubkey>,
+        1 + // pub reserve_surplus_destination: ReserveSurplusDestination,
+        1 + // pub has_fee_split: bool,
+        8 + // pub protocol_fee_to_collect_quantity: u64,
+        1 + 32 + // pub allowlist_admin: Option<Pubkey>,
+        2 + // allowlist_restricted_ixs: u16,
+        2 * (1 + 8) + // pub wallet_deposit_cap: [Option<u64>; 2],
+        1 + // pub deposit_cap_mode: DepositCapMode,
+        1 + 8 + // pub total_deposit_cap: Option<u64>,
+        2 * (1 + 8) + // coverage_ratio_bps_bounds: [Option<u64>; 2],
+        TrancheLifecycle::LEN + // pub lifecycle: TrancheLifecycle,
+        1 + // pub settled: bool,
+        8 + // pub epoch: u64,
+        8 + // pub queued_reserve_quantity: u64,
+        1 + // pub orphaned_quantity_destination: OrphanedQuantityDestination,
+        2 * 8 + // pub min_initial_deposit: [u64; 2],
+        8 + // pub dead_shares_quantity: u64,
+        1 + // pub reserve_mint_decimals: u8,
+        1 + // pub tranche_mint_decimals: u8,
+        1; // pub has_user_deposit_cap_override: bool,
 }
@@ -28,26 +28,34 @@ pub struct TrancheData {
     /// flags for owner-only instructions
     owner_restricted_ix: u16,
 
-    /// deposit cap for senior side and junior side, if the value is None the cap is disabled
+    /// deposit cap for senior side and junior side, expressed in the tranche config deposit_cap_mode unit, if the value is None the cap is disabled
     pub deposit_cap: [Option<u64>; 2],
+
+    /// entry fee for senior side and junior side, expressed in basis points of the deposited reserve
+    entry_fee_bps: [u16; 2],
+
+    /// exit fee for senior side and junior side, expressed in basis points of the redeemed reserve
+    exit_fee_bps: [u16; 2],
 }
 
 impl TrancheData {
-    pub fn new(slot: u64) -> Self {
+    pub fn new(slot: u64, unix_timestamp: i64) -> Self {
         Self {
             deposited_quantity: [0; 2],
             deposit_cap: [None; 2],
             reserve_fair_value: ReserveFairValue {
                 value: [dec!(1).serialize(); 10],
-                slot_tracking: SlotTracking::new(slot),
+                slot_tracking: SlotTracking::new(slot, unix_timestamp),
             },
             tranche_fair_value: TrancheFairValue {
                 value: [dec!(1).serialize(); 2],
-                slot_tracking: SlotTracking::new(slot),
+                slot_tracking: SlotTracking::new(slot, unix_timestamp),
             },
             halt_flags: 0,
             owner_restricted_ix: 0,
             fee_to_collect_quantity: 0,
+            entry_fee_bps: [0; 2],
+            exit_fee_bps: [0; 2],
         }
     }
 
@@ -74,6 +82,66 @@ impl TrancheData {
         Ok(())
     }
 
+    pub fn get_entry_fee_bps(&self) -> [u16; 2] {
+        self.entry_fee_bps
+    }
+
+    pub fn set_entry_fee_bps(&mut self, entry_fee_bps: [u16; 2]) -> Result<()> {
+        Self::check_fee_bps(&entry_fee_bps)?;
+        self.entry_fee_bps = entry_fee_bps;
+        Ok(())
+    }
+
+    pub fn get_exit_fee_bps(&self) -> [u16; 2] {
+        self.exit_fee_bps
+    }
+
+    pub fn set_exit_fee_bps(&mut self, exit_fee_bps: [u16; 2]) -> Result<()> {
+        Self::check_fee_bps(&exit_fee_bps)?;
+        self.exit_fee_bps = exit_fee_bps;
+        Ok(())
+    }
+
+    /// Entry fee due on the reserve quantity deposited on the given side
+    pub fn get_entry_fee(&self, side: usize, reserve_quantity: u64) -> Result<u64> {
+        Self::compute_fee(reserve_quantity, self.entry_fee_bps[side])
+    }
+
+    /// Exit fee due on the reserve quantity redeemed from the given side
+    pub fn get_exit_fee(&self, side: usize, reserve_quantity: u64) -> Result<u64> {
+        Self::compute_fee(reserve_quantity, self.exit_fee_bps[side])
+    }
+
+    fn check_fee_bps(fee_bps: &[u16; 2]) -> Result<()> {
+        if fee_bps.iter().any(|&bps| bps > 10_000) {
+            msg!("fee can't exceed 10_000 bps");
+            return err!(VyperErrorCode::InvalidInput);
+        }
+        Ok(())
+    }
+
+    fn compute_fee(quantity: u64, fee_bps: u16) -> Result<u64> {
+        u64::try_from(
+            (quantity as u128)
+                .checked_mul(fee_bps as u128)
+                .ok_or(VyperErrorCode::MathError)?
+                / 10_000,
+        )
+        .map_err(|_| VyperErrorCode::MathError.into())
+    }
+
+    /// Reserve quantity held in the vault but not tracked by deposited quantities or fees
+    pub fn get_reserve_surplus(&self, reserve_amount: u64) -> Result<u64> {
+        let tracked_quantity = self
+            .deposited_quantity
+            .iter()
+            .try_fold(self.fee_to_collect_quantity, |acc, &qty| {
+                acc.checked_add(qty)
+            })
+            .ok_or(VyperErrorCode::MathError)?;
+        Ok(reserve_amount.saturating_sub(tracked_quantity))
+    }
+
     pub const LEN: usize = 2*8 + // pub deposited_quantity: [u64; 2],
     8 + // pub fee_to_collect_quantity: u64,
     2 * (1 + 8) + // pub deposit_cap: [Option<u64>; 2],
@@ -81,5 +149,7 @@ impl TrancheData {
     TrancheFairValue::LEN + // pub tranche_fair_value: TrancheFairValue,
     2 + // halt_flags: u16,
     2 + // owner_restricted_ix: u16,
-    64; // padding
+    2 * 2 + // entry_fee_bps: [u16; 2],
+    2 * 2 + // exit_fee_bps: [u16; 2],
+    56; // padding
 }
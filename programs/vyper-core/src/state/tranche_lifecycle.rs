@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VyperErrorCode;
+
+/// Phase of the tranche lifecycle at a given time
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranchePhase {
+    /// Subscription not started yet
+    PreSubscription,
+
+    /// Deposits accepted
+    Subscription,
+
+    /// Subscription ended, maturity not reached yet
+    Active,
+
+    /// Maturity reached
+    Matured,
+}
+
+/// Optional lifecycle timestamps, all the values are unix timestamps and None disables the bound
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrancheLifecycle {
+    /// deposits are accepted from this timestamp
+    pub subscription_start: Option<i64>,
+
+    /// deposits are rejected from this timestamp
+    pub subscription_end: Option<i64>,
+
+    /// redeems are accepted from this timestamp
+    pub maturity: Option<i64>,
+
+    /// redeems are accepted before maturity too
+    pub redeem_before_maturity: bool,
+}
+
+impl TrancheLifecycle {
+    /// Check that the timestamps are ordered: subscription start, subscription end, maturity
+    pub fn is_valid(&self) -> bool {
+        let timestamps: Vec<i64> = [
+            self.subscription_start,
+            self.subscription_end,
+            self.maturity,
+        ]
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+        timestamps.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    pub fn get_phase(&self, unix_timestamp: i64) -> TranchePhase {
+        if matches!(self.maturity, Some(maturity) if unix_timestamp >= maturity) {
+            TranchePhase::Matured
+        } else if matches!(self.subscription_end, Some(subscription_end) if unix_timestamp >= subscription_end)
+        {
+            TranchePhase::Active
+        } else if matches!(self.subscription_start, Some(subscription_start) if unix_timestamp < subscription_start)
+        {
+            TranchePhase::PreSubscription
+        } else {
+            TranchePhase::Subscription
+        }
+    }
+
+    /// Deposits are accepted only during the subscription
+    pub fn check_deposit_allowed(&self, unix_timestamp: i64) -> Result<()> {
+        match self.get_phase(unix_timestamp) {
+            TranchePhase::Subscription => Ok(()),
+            TranchePhase::PreSubscription => err!(VyperErrorCode::SubscriptionNotStarted),
+            TranchePhase::Active | TranchePhase::Matured => {
+                err!(VyperErrorCode::SubscriptionEnded)
+            }
+        }
+    }
+
+    /// Redeems are accepted after maturity, unless redeems before maturity are enabled
+    pub fn check_redeem_allowed(&self, unix_timestamp: i64) -> Result<()> {
+        if self.redeem_before_maturity
+            || self.get_phase(unix_timestamp) == TranchePhase::Matured
+            || self.maturity.is_none()
+        {
+            Ok(())
+        } else {
+            err!(VyperErrorCode::TrancheNotMatured)
+        }
+    }
+
+    pub const LEN: usize = 1 + 8 + // pub subscription_start: Option<i64>,
+    1 + 8 + // pub subscription_end: Option<i64>,
+    1 + 8 + // pub maturity: Option<i64>,
+    1; // pub redeem_before_maturity: bool,
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::VyperErrorCode;
+
+/// Reserve deposited by a single wallet in a tranche config, used to enforce the wallet deposit caps
+#[account]
+pub struct UserPosition {
+    /// Tranche config the position belongs to
+    pub tranche_config: Pubkey,
+
+    /// Wallet owning the position
+    pub user: Pubkey,
+
+    /// Reserve quantity currently deposited by the wallet, for senior and junior side
+    pub deposited_quantity: [u64; 2],
+
+    /// Wallet specific deposit cap set by the owner, if None the tranche config default is used
+    pub deposit_cap_override: Option<[Option<u64>; 2]>,
+}
+
+impl UserPosition {
+    /// Address of the position of the user in the tranche config
+    pub fn get_address(tranche_config: Pubkey, user: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                tranche_config.as_ref(),
+                b"user_position".as_ref(),
+                user.as_ref(),
+            ],
+            &crate::ID,
+        )
+        .0
+    }
+
+    /// Load the position of the user from the given accounts, looked up by address.
+    /// Fails if the writable position account is missing, returns None if the position has not been created
+    pub fn load<'info>(
+        accounts: &[AccountInfo<'info>],
+        tranche_config: Pubkey,
+        user: Pubkey,
+    ) -> Result<Option<Account<'info, UserPosition>>> {
+        let user_position_key = Self::get_address(tranche_config, user);
+        let account = accounts
+            .iter()
+            .find(|account| account.key() == user_position_key && account.is_writable)
+            .ok_or(VyperErrorCode::MissingUserPosition)?;
+        if *account.owner != crate::ID {
+            return Ok(None);
+        }
+        Ok(Some(Account::<UserPosition>::try_from(account)?))
+    }
+
+    /// Track a deposit, failing if it exceeds the given wallet deposit cap
+    pub fn add_deposit(
+        &mut self,
+        net_reserve_quantity: [u64; 2],
+        wallet_deposit_cap: [Option<u64>; 2],
+    ) -> Result<()> {
+        for (i, &net_reserve_qty) in net_reserve_quantity.iter().enumerate() {
+            let new_deposited_qty = self.deposited_quantity[i]
+                .checked_add(net_reserve_qty)
+                .ok_or(VyperErrorCode::MathError)?;
+            if let Some(deposit_cap) = wallet_deposit_cap[i] {
+                if new_deposited_qty > deposit_cap {
+                    return err!(VyperErrorCode::WalletDepositExceededCap);
+                }
+            }
+            self.deposited_quantity[i] = new_deposited_qty;
+        }
+        Ok(())
+    }
+
+    /// Release the wallet deposit cap after a redeem
+    pub fn remove_deposit(&mut self, redeemed_reserve_quantity: [u64; 2]) {
+        for (i, &redeemed_reserve_qty) in redeemed_reserve_quantity.iter().enumerate() {
+            self.deposited_quantity[i] =
+                self.deposited_quantity[i].saturating_sub(redeemed_reserve_qty);
+        }
+    }
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pub tranche_config: Pubkey,
+        32 + // pub user: Pubkey,
+        2 * 8 + // pub deposited_quantity: [u64; 2],
+        1 + 2 * (1 + 8); // pub deposit_cap_override: Option<[Option<u64>; 2]>,
+}
@@ -3,3 +3,18 @@ use anchor_lang::prelude::*;
 pub trait Input {
     fn is_valid(&self) -> Result<()>;
 }
+
+/// Current program version: [major, minor, patch]
+pub fn get_version_arr() -> [u8; 3] {
+    [
+        env!("CARGO_PKG_VERSION_MAJOR")
+            .parse::<u8>()
+            .expect("failed to parse major version"),
+        env!("CARGO_PKG_VERSION_MINOR")
+            .parse::<u8>()
+            .expect("failed to parse minor version"),
+        env!("CARGO_PKG_VERSION_PATCH")
+            .parse::<u8>()
+            .expect("failed to parse patch version"),
+    ]
+}
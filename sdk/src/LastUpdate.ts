@@ -1,10 +1,10 @@
 import * as anchor from '@project-serum/anchor'
 export class LastUpdate {
     slot: number;
-    padding: number[];
+    unixTimestamp: number;
 
-    constructor(slot: anchor.BN, padding: number[]) {
+    constructor(slot: anchor.BN, unixTimestamp: anchor.BN) {
         this.slot = slot.toNumber();
-        this.padding = padding;
+        this.unixTimestamp = unixTimestamp.toNumber();
     }
 }
\ No newline at end of file
@@ -4,9 +4,13 @@ import { LastUpdate } from "./LastUpdate";
 export class SlotTracking {
     lastUpdate: LastUpdate;
     staleSlotThreshold: number;
+    staleSecondsThreshold: number;
+    stalenessMode: object;
 
-    constructor(lastUpdate: LastUpdate, staleSlotThreshold: anchor.BN) {
+    constructor(lastUpdate: LastUpdate, staleSlotThreshold: anchor.BN, staleSecondsThreshold: anchor.BN, stalenessMode: object) {
         this.lastUpdate = lastUpdate
         this.staleSlotThreshold = staleSlotThreshold.toNumber();
+        this.staleSecondsThreshold = staleSecondsThreshold.toNumber();
+        this.stalenessMode = stalenessMode;
     }
 }
@@ -57,17 +57,21 @@ export class Vyper {
         const slotTrackingReserve = new SlotTracking(
             new LastUpdate(
                 trancheInfo.trancheData.reserveFairValue['slotTracking']['lastUpdate']['slot'],
-                trancheInfo.trancheData.reserveFairValue['slotTracking']['lastUpdate']['padding']
+                trancheInfo.trancheData.reserveFairValue['slotTracking']['lastUpdate']['unixTimestamp']
             ),
-            trancheInfo.trancheData.reserveFairValue['slotTracking']['staleSlotThreshold']
+            trancheInfo.trancheData.reserveFairValue['slotTracking']['staleSlotThreshold'],
+            trancheInfo.trancheData.reserveFairValue['slotTracking']['staleSecondsThreshold'],
+            trancheInfo.trancheData.reserveFairValue['slotTracking']['stalenessMode']
         );
 
         const slotTrackingTranche = new SlotTracking(
             new LastUpdate(
                 trancheInfo.trancheData.trancheFairValue['slotTracking']['lastUpdate']['slot'],
-                trancheInfo.trancheData.trancheFairValue['slotTracking']['lastUpdate']['padding']
+                trancheInfo.trancheData.trancheFairValue['slotTracking']['lastUpdate']['unixTimestamp']
             ),
-            trancheInfo.trancheData.trancheFairValue['slotTracking']['staleSlotThreshold']
+            trancheInfo.trancheData.trancheFairValue['slotTracking']['staleSlotThreshold'],
+            trancheInfo.trancheData.trancheFairValue['slotTracking']['staleSecondsThreshold'],
+            trancheInfo.trancheData.trancheFairValue['slotTracking']['stalenessMode']
         );
 
         const reserveFairValue = new ReserveFairValue(
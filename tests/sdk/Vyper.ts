@@ -16,6 +16,7 @@ export class Vyper {
     seniorTrancheMint: PublicKey;
     trancheConfig: PublicKey;
     trancheAuthority: PublicKey;
+    protocolConfig: PublicKey;
     reserveMint: PublicKey;
     reserve: PublicKey;
     ratePlugin: PublicKey;
@@ -46,6 +47,10 @@ export class Vyper {
             [trancheConfig.publicKey.toBuffer(), anchor.utils.bytes.utf8.encode("authority")],
             this.program.programId
         );
+        const [protocolConfig] = await anchor.web3.PublicKey.findProgramAddress(
+            [anchor.utils.bytes.utf8.encode("protocol_config")],
+            this.program.programId
+        );
         const [reserve] = await anchor.web3.PublicKey.findProgramAddress(
             [trancheConfig.publicKey.toBuffer(), reserveMint.toBuffer()],
             this.program.programId
@@ -74,6 +79,7 @@ export class Vyper {
         this.juniorTrancheMint = juniorTrancheMint.publicKey;
         this.trancheConfig = trancheConfig.publicKey;
         this.trancheAuthority = trancheAuthority;
+        this.protocolConfig = protocolConfig;
         this.reserveMint = reserveMint;
         this.reserve = reserve;
         this.ratePlugin = ratePlugin;
@@ -88,6 +94,23 @@ export class Vyper {
             .accounts({
                 signer: this.provider.wallet.publicKey,
                 trancheConfig: this.trancheConfig,
+                protocolConfig: this.protocolConfig,
+                seniorTrancheMint: this.seniorTrancheMint,
+                juniorTrancheMint: this.juniorTrancheMint,
+                rateProgramState: this.ratePluginState,
+                redeemLogicProgram: this.redeemLogicPlugin,
+                redeemLogicProgramState: this.redeemLogicPluginState,
+            })
+            .instruction();
+    }
+
+    async getSettleIX(): Promise<anchor.web3.TransactionInstruction> {
+        return await this.program.methods
+            .settle()
+            .accounts({
+                signer: this.provider.wallet.publicKey,
+                trancheConfig: this.trancheConfig,
+                protocolConfig: this.protocolConfig,
                 seniorTrancheMint: this.seniorTrancheMint,
                 juniorTrancheMint: this.juniorTrancheMint,
                 rateProgramState: this.ratePluginState,
@@ -103,6 +126,7 @@ export class Vyper {
             .accounts({
                 signer: this.provider.wallet.publicKey,
                 trancheConfig: this.trancheConfig,
+                protocolConfig: this.protocolConfig,
                 seniorTrancheMint: this.seniorTrancheMint,
                 juniorTrancheMint: this.juniorTrancheMint,
                 rateProgramState: this.ratePluginState,
@@ -112,20 +136,81 @@ export class Vyper {
             .rpc();
     }
 
+    async getDeadSharesAccounts(): Promise<[PublicKey, PublicKey]> {
+        const [seniorTrancheDeadShares] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                this.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("dead_shares"),
+                this.seniorTrancheMint.toBuffer(),
+            ],
+            this.program.programId
+        );
+        const [juniorTrancheDeadShares] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                this.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("dead_shares"),
+                this.juniorTrancheMint.toBuffer(),
+            ],
+            this.program.programId
+        );
+        return [seniorTrancheDeadShares, juniorTrancheDeadShares];
+    }
+
+    async initDeadShares() {
+        const [seniorTrancheDeadShares, juniorTrancheDeadShares] = await this.getDeadSharesAccounts();
+        await this.program.methods
+            .initDeadShares()
+            .accounts({
+                payer: this.provider.wallet.publicKey,
+                trancheConfig: this.trancheConfig,
+                trancheAuthority: this.trancheAuthority,
+                seniorTrancheMint: this.seniorTrancheMint,
+                juniorTrancheMint: this.juniorTrancheMint,
+                seniorTrancheDeadShares,
+                juniorTrancheDeadShares,
+            })
+            .rpc();
+    }
+
+    async getCloseRemainingAccounts(): Promise<anchor.web3.AccountMeta[]> {
+        const seeds = ["pending_plugin_update", "pending_tranche_data_update", "fee_split", "pending_epoch_roll"];
+        const accounts: anchor.web3.AccountMeta[] = [];
+        for (const seed of seeds) {
+            const [pubkey] = await anchor.web3.PublicKey.findProgramAddress(
+                [this.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode(seed)],
+                this.program.programId
+            );
+            accounts.push({ pubkey, isSigner: false, isWritable: true });
+        }
+        return accounts;
+    }
+
+    getRefreshRemainingAccounts(): anchor.web3.AccountMeta[] {
+        return [
+            { pubkey: this.ratePluginState, isSigner: false, isWritable: false },
+            { pubkey: this.redeemLogicPlugin, isSigner: false, isWritable: false },
+            { pubkey: this.redeemLogicPluginState, isSigner: false, isWritable: false },
+        ];
+    }
+
     async getDepositIx(
         seniorDepositAmount: number,
         juniorDepositAmount: number,
         userReserveToken: PublicKey,
         userSeniorTrancheTokenAccount: PublicKey,
-        userJuniorTrancheTokenAccount: PublicKey
+        userJuniorTrancheTokenAccount: PublicKey,
+        minTrancheOut: [number | null, number | null] = [null, null],
+        refreshOnDemand: boolean = false
     ): Promise<anchor.web3.TransactionInstruction> {
         return await this.program.methods
             .deposit({
                 reserveQuantity: [new anchor.BN(seniorDepositAmount), new anchor.BN(juniorDepositAmount)],
+                minTrancheOut: minTrancheOut.map((v) => (v === null ? null : new anchor.BN(v))),
             })
             .accounts({
                 signer: this.provider.wallet.publicKey,
                 trancheConfig: this.trancheConfig,
+                protocolConfig: this.protocolConfig,
                 trancheAuthority: this.trancheAuthority,
                 reserve: this.reserve,
                 userReserveToken,
@@ -134,6 +219,7 @@ export class Vyper {
                 seniorTrancheDest: userSeniorTrancheTokenAccount,
                 juniorTrancheDest: userJuniorTrancheTokenAccount,
             })
+            .remainingAccounts(refreshOnDemand ? this.getRefreshRemainingAccounts() : [])
             .instruction();
     }
 
@@ -142,15 +228,19 @@ export class Vyper {
         juniorDepositAmount: number,
         userReserveToken: anchor.web3.PublicKey,
         seniorTrancheTokenAccount: anchor.web3.PublicKey,
-        juniorTrancheTokenAccount: anchor.web3.PublicKey
+        juniorTrancheTokenAccount: anchor.web3.PublicKey,
+        minReserveOut: number | null = null,
+        refreshOnDemand: boolean = false
     ): Promise<anchor.web3.TransactionInstruction> {
         return await this.program.methods
             .redeem({
                 trancheQuantity: [new anchor.BN(seniorDepositAmount), new anchor.BN(juniorDepositAmount)],
+                minReserveOut: minReserveOut === null ? null : new anchor.BN(minReserveOut),
             })
             .accounts({
                 signer: this.provider.wallet.publicKey,
                 trancheConfig: this.trancheConfig,
+                protocolConfig: this.protocolConfig,
                 trancheAuthority: this.trancheAuthority,
                 reserve: this.reserve,
                 userReserveToken,
@@ -159,6 +249,7 @@ export class Vyper {
                 seniorTrancheSource: seniorTrancheTokenAccount,
                 juniorTrancheSource: juniorTrancheTokenAccount,
             })
+            .remainingAccounts(refreshOnDemand ? this.getRefreshRemainingAccounts() : [])
             .instruction();
     }
 }
@@ -112,6 +112,36 @@ export const UPDATE_TRANCHE_CONFIG_FLAGS = {
     RESERVE_FAIR_VALUE_STALE_SLOT_THRESHOLD: 1 << 2,
     TRANCHE_FAIR_VALUE_STALE_SLOT_THRESHOLD: 1 << 3,
     DEPOSIT_CAP: 1 << 4,
+    TIMELOCK_SLOTS: 1 << 5,
+    GUARDIAN: 1 << 6,
+    RESERVE_SURPLUS_DESTINATION: 1 << 7,
+    ENTRY_FEE_BPS: 1 << 8,
+    EXIT_FEE_BPS: 1 << 9,
+    ALLOWLIST_RESTRICTED_IXS: 1 << 10,
+    ALLOWLIST_ADMIN: 1 << 11,
+    WALLET_DEPOSIT_CAP: 1 << 12,
+    DEPOSIT_CAP_MODE: 1 << 13,
+    TOTAL_DEPOSIT_CAP: 1 << 14,
+    COVERAGE_RATIO_BPS_BOUNDS: 1 << 15,
+};
+
+// flags passed in bitmaskExt, shifted by 16 bits
+export const UPDATE_TRANCHE_CONFIG_EXT_FLAGS = {
+    LIFECYCLE: 1 << 0,
+    RESERVE_FAIR_VALUE_STALE_SECONDS_THRESHOLD: 1 << 1,
+    TRANCHE_FAIR_VALUE_STALE_SECONDS_THRESHOLD: 1 << 2,
+    RESERVE_FAIR_VALUE_STALENESS_MODE: 1 << 3,
+    TRANCHE_FAIR_VALUE_STALENESS_MODE: 1 << 4,
+    ORPHANED_QUANTITY_DESTINATION: 1 << 5,
+    MIN_INITIAL_DEPOSIT: 1 << 6,
+    DEAD_SHARES_QUANTITY: 1 << 7,
+};
+
+export const UPDATE_PROTOCOL_CONFIG_FLAGS = {
+    ADMIN: 1 << 0,
+    PROTOCOL_FEE_BPS: 1 << 1,
+    TREASURY: 1 << 2,
+    HALT_FLAGS: 1 << 3,
 };
 
 export const TRANCHE_HALT_FLAGS = {
@@ -133,3 +163,9 @@ export const OWNER_RESTRICTED_IX_FLAGS = {
 
 export const OWNER_RESTRICTED_IX_FLAGS_ALL =
     OWNER_RESTRICTED_IX_FLAGS.DEPOSITS | OWNER_RESTRICTED_IX_FLAGS.REDEEMS | OWNER_RESTRICTED_IX_FLAGS.REFRESHES;
+
+export const ALLOWLIST_RESTRICTED_IX_FLAGS = {
+    NONE: 0,
+    DEPOSITS: 1 << 0,
+    REDEEMS: 1 << 1,
+};
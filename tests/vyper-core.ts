@@ -1,6 +1,6 @@
 import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
-import { getAccount, getMint, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { createTransferInstruction, getAccount, getMint, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { RustDecimalWrapper } from "@vyper-protocol/rust-decimal-wrapper";
 import { assert, expect } from "chai";
 import { OwnerRestrictedIxFlags } from "../sdk/src/OwnerRestrictedIxFlags";
@@ -17,9 +17,12 @@ import {
     createTokenAccount,
     getInitializeData,
     getTokenAccountAmount,
+    ALLOWLIST_RESTRICTED_IX_FLAGS,
     TRANCHE_HALT_FLAGS,
     TRANCHE_HALT_FLAGS_HALT_ALL,
+    UPDATE_PROTOCOL_CONFIG_FLAGS,
     UPDATE_TRANCHE_CONFIG_FLAGS,
+    UPDATE_TRANCHE_CONFIG_EXT_FLAGS,
 } from "./utils";
 
 describe.only("vyper_core", () => {
@@ -31,6 +34,30 @@ describe.only("vyper_core", () => {
     const programRedeemLogicLending = anchor.workspace.RedeemLogicLending as Program<RedeemLogicLending>;
     const programRateMock = anchor.workspace.RateMock as Program<RateMock>;
 
+    before(async () => {
+        const [protocolConfig] = await anchor.web3.PublicKey.findProgramAddress(
+            [anchor.utils.bytes.utf8.encode("protocol_config")],
+            programVyperCore.programId
+        );
+        if ((await provider.connection.getAccountInfo(protocolConfig)) == null) {
+            const [programData] = await anchor.web3.PublicKey.findProgramAddress(
+                [programVyperCore.programId.toBuffer()],
+                new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+            );
+            await programVyperCore.methods
+                .initializeProtocolConfig({ protocolFeeBps: 0, haltFlags: 0 })
+                .accounts({
+                    upgradeAuthority: provider.wallet.publicKey,
+                    protocolConfig,
+                    admin: provider.wallet.publicKey,
+                    treasury: provider.wallet.publicKey,
+                    program: programVyperCore.programId,
+                    programData,
+                })
+                .rpc();
+        }
+    });
+
     it.only("initialize", async () => {
         const trancheMintDecimals = 6;
         const reserveMint = await createMint(provider);
@@ -201,6 +228,26 @@ describe.only("vyper_core", () => {
                 reserveFairValueStaleSlotThreshold: bn(2),
                 trancheFairValueStaleSlotThreshold: bn(2),
                 depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
             })
             .accounts({
                 owner: provider.wallet.publicKey,
@@ -219,6 +266,26 @@ describe.only("vyper_core", () => {
                 reserveFairValueStaleSlotThreshold: bn(2),
                 trancheFairValueStaleSlotThreshold: bn(2),
                 depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
             })
             .accounts({
                 owner: provider.wallet.publicKey,
@@ -285,6 +352,26 @@ describe.only("vyper_core", () => {
                 reserveFairValueStaleSlotThreshold: bn(newStaleSlotThreshold),
                 trancheFairValueStaleSlotThreshold: bn(newStaleSlotThreshold),
                 depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
             })
             .accounts({
                 owner: provider.wallet.publicKey,
@@ -621,6 +708,26 @@ describe.only("vyper_core", () => {
                 reserveFairValueStaleSlotThreshold: bn(0),
                 trancheFairValueStaleSlotThreshold: bn(0),
                 depositCap: [bn(seniorDepositCap), bn(juniorDepositCap)],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
             })
             .accounts({
                 owner: provider.wallet.publicKey,
@@ -658,4 +765,3301 @@ describe.only("vyper_core", () => {
             assert(true);
         }
     });
+
+    it("transfer ownership", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const newOwner = anchor.web3.Keypair.generate();
+
+        await programVyperCore.methods
+            .proposeOwner()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                newOwner: newOwner.publicKey,
+            })
+            .rpc();
+
+        let trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.owner.toBase58()).to.eql(provider.wallet.publicKey.toBase58());
+        expect(trancheConfigAccount.pendingOwner.toBase58()).to.eql(newOwner.publicKey.toBase58());
+
+        try {
+            await programVyperCore.methods
+                .acceptOwner()
+                .accounts({
+                    newOwner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                })
+                .rpc();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await programVyperCore.methods
+            .acceptOwner()
+            .accounts({
+                newOwner: newOwner.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .signers([newOwner])
+            .rpc();
+
+        trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.owner.toBase58()).to.eql(newOwner.publicKey.toBase58());
+        expect(trancheConfigAccount.pendingOwner).to.be.null;
+    });
+
+    it("apply plugin update", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        let newRateMock = RateMockPlugin.create(programRateMock, provider);
+        await newRateMock.initialize();
+
+        const [pendingPluginUpdate] = await anchor.web3.PublicKey.findProgramAddress(
+            [vyper.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode("pending_plugin_update")],
+            programVyperCore.programId
+        );
+
+        await programVyperCore.methods
+            .queuePluginUpdate()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingPluginUpdate,
+                rateProgram: newRateMock.programID,
+                rateProgramState: newRateMock.state,
+                redeemLogicProgram: redeemLogic.programID,
+                redeemLogicProgramState: redeemLogic.state,
+            })
+            .rpc();
+
+        await programVyperCore.methods
+            .applyPluginUpdate()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingPluginUpdate,
+            })
+            .rpc();
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.rateProgramState.toBase58()).to.eql(newRateMock.state.toBase58());
+        expect(trancheConfigAccount.redeemLogicProgramState.toBase58()).to.eql(redeemLogic.state.toBase58());
+        expect(await provider.connection.getAccountInfo(pendingPluginUpdate)).to.be.null;
+    });
+
+    it("expect error on plugin update before timelock", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.TIMELOCK_SLOTS,
+                haltFlags: TRANCHE_HALT_FLAGS.NONE,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(0),
+                trancheFairValueStaleSlotThreshold: bn(0),
+                depositCap: [null, null],
+                timelockSlots: bn(1000),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        let newRateMock = RateMockPlugin.create(programRateMock, provider);
+        await newRateMock.initialize();
+
+        const [pendingPluginUpdate] = await anchor.web3.PublicKey.findProgramAddress(
+            [vyper.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode("pending_plugin_update")],
+            programVyperCore.programId
+        );
+
+        await programVyperCore.methods
+            .queuePluginUpdate()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingPluginUpdate,
+                rateProgram: newRateMock.programID,
+                rateProgramState: newRateMock.state,
+                redeemLogicProgram: redeemLogic.programID,
+                redeemLogicProgramState: redeemLogic.state,
+            })
+            .rpc();
+
+        try {
+            await programVyperCore.methods
+                .applyPluginUpdate()
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                    pendingPluginUpdate,
+                })
+                .rpc();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await programVyperCore.methods
+            .cancelPluginUpdate()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingPluginUpdate,
+            })
+            .rpc();
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.rateProgramState.toBase58()).to.eql(rateMock.state.toBase58());
+        expect(await provider.connection.getAccountInfo(pendingPluginUpdate)).to.be.null;
+    });
+
+    it("queue loosening tranche data update", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const haltDepositsUpdate = {
+            bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.HALT_FLAGS | UPDATE_TRANCHE_CONFIG_FLAGS.TIMELOCK_SLOTS,
+            haltFlags: TRANCHE_HALT_FLAGS.HALT_DEPOSITS,
+            ownerRestrictedIxs: 0,
+            reserveFairValueStaleSlotThreshold: bn(0),
+            trancheFairValueStaleSlotThreshold: bn(0),
+            depositCap: [null, null],
+            timelockSlots: bn(1000),
+            guardian: null,
+            reserveSurplusDestination: { untracked: {} },
+            entryFeeBps: [0, 0],
+            exitFeeBps: [0, 0],
+            allowlistRestrictedIxs: 0,
+            allowlistAdmin: null,
+            walletDepositCap: [null, null],
+            depositCapMode: { quantity: {} },
+            totalDepositCap: null,
+            coverageRatioBpsBounds: [null, null],
+            lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+            reserveFairValueStaleSecondsThreshold: bn(0),
+            trancheFairValueStaleSecondsThreshold: bn(0),
+            reserveFairValueStalenessMode: { slots: {} },
+            trancheFairValueStalenessMode: { slots: {} },
+            orphanedQuantityDestination: { tranche: {} },
+            minInitialDeposit: [bn(0), bn(0)],
+            deadSharesQuantity: bn(0),
+            bitmaskExt: 0,
+        };
+        const liftHaltUpdate = {
+            ...haltDepositsUpdate,
+            bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.HALT_FLAGS,
+            haltFlags: TRANCHE_HALT_FLAGS.NONE,
+        };
+
+        // tightening changes are applied immediately
+        await programVyperCore.methods
+            .updateTrancheData(haltDepositsUpdate)
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        let trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.haltFlags).to.eql(TRANCHE_HALT_FLAGS.HALT_DEPOSITS);
+        expect(trancheConfigAccount.timelockSlots.toNumber()).to.eql(1000);
+
+        // loosening changes need to be queued
+        try {
+            await programVyperCore.methods
+                .updateTrancheData(liftHaltUpdate)
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                })
+                .rpc();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const [pendingTrancheDataUpdate] = await anchor.web3.PublicKey.findProgramAddress(
+            [vyper.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode("pending_tranche_data_update")],
+            programVyperCore.programId
+        );
+
+        await programVyperCore.methods
+            .queueTrancheDataUpdate(liftHaltUpdate)
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingTrancheDataUpdate,
+            })
+            .rpc();
+
+        try {
+            await programVyperCore.methods
+                .applyTrancheDataUpdate()
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                    pendingTrancheDataUpdate,
+                })
+                .rpc();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await programVyperCore.methods
+            .cancelTrancheDataUpdate()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingTrancheDataUpdate,
+            })
+            .rpc();
+
+        trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.haltFlags).to.eql(TRANCHE_HALT_FLAGS.HALT_DEPOSITS);
+        expect(await provider.connection.getAccountInfo(pendingTrancheDataUpdate)).to.be.null;
+    });
+
+    it("guardian can halt but not unhalt", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: TRANCHE_HALT_FLAGS.HALT_REDEEMS },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const guardian = anchor.web3.Keypair.generate();
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.GUARDIAN,
+                haltFlags: TRANCHE_HALT_FLAGS.NONE,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(0),
+                trancheFairValueStaleSlotThreshold: bn(0),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: guardian.publicKey,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        await programVyperCore.methods
+            .guardianHalt(TRANCHE_HALT_FLAGS.HALT_DEPOSITS)
+            .accounts({
+                guardian: guardian.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .signers([guardian])
+            .rpc();
+
+        // halt flags are added to the current ones
+        let trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.haltFlags).to.eql(
+            TRANCHE_HALT_FLAGS.HALT_DEPOSITS | TRANCHE_HALT_FLAGS.HALT_REDEEMS
+        );
+
+        await programVyperCore.methods
+            .guardianHalt(TRANCHE_HALT_FLAGS.NONE)
+            .accounts({
+                guardian: guardian.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .signers([guardian])
+            .rpc();
+
+        trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.haltFlags).to.eql(
+            TRANCHE_HALT_FLAGS.HALT_DEPOSITS | TRANCHE_HALT_FLAGS.HALT_REDEEMS
+        );
+
+        try {
+            await programVyperCore.methods
+                .guardianHalt(TRANCHE_HALT_FLAGS.HALT_REFRESHES)
+                .accounts({
+                    guardian: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                })
+                .rpc();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+    });
+
+    it("close tranche", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const destReserve = await createTokenAccount(provider, reserveMint, provider.wallet.publicKey);
+
+        await programVyperCore.methods
+            .closeTranche()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                reserve: vyper.reserve,
+                destReserve,
+                seniorTrancheMint: vyper.seniorTrancheMint,
+                juniorTrancheMint: vyper.juniorTrancheMint,
+                destination: provider.wallet.publicKey,
+            })
+            .remainingAccounts(await vyper.getCloseRemainingAccounts())
+            .rpc();
+
+        expect(await provider.connection.getAccountInfo(vyper.trancheConfig)).to.be.null;
+        expect(await provider.connection.getAccountInfo(vyper.reserve)).to.be.null;
+        expect((await getMint(provider.connection, vyper.seniorTrancheMint)).mintAuthority).to.be.null;
+        expect((await getMint(provider.connection, vyper.juniorTrancheMint)).mintAuthority).to.be.null;
+    });
+
+    it("collect fees sweeps reserve surplus", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const donationAmount = 10 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount + donationAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.RESERVE_SURPLUS_DESTINATION,
+                haltFlags: TRANCHE_HALT_FLAGS.NONE,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(0),
+                trancheFairValueStaleSlotThreshold: bn(0),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { fee: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        depositTx.add(
+            createTransferInstruction(userReserveToken, vyper.reserve, provider.wallet.publicKey, donationAmount)
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        const destReserve = await createTokenAccount(provider, reserveMint, anchor.web3.Keypair.generate().publicKey);
+        await programVyperCore.methods
+            .collectFee()
+            .accounts({
+                trancheConfig: vyper.trancheConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                reserve: vyper.reserve,
+                destReserve,
+                owner: provider.wallet.publicKey,
+            })
+            .rpc();
+
+        expect(await getTokenAccountAmount(provider, destReserve)).to.eql(donationAmount);
+        expect(await getTokenAccountAmount(provider, vyper.reserve)).to.eql(seniorDepositAmount + juniorDepositAmount);
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.eql(0);
+    });
+
+    it("collect fees through fee split", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5, 15); // with fee
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const curatorReserve = await createTokenAccount(provider, reserveMint, anchor.web3.Keypair.generate().publicKey);
+        const treasuryReserve = await createTokenAccount(provider, reserveMint, anchor.web3.Keypair.generate().publicKey);
+        const [feeSplit] = await anchor.web3.PublicKey.findProgramAddress(
+            [vyper.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode("fee_split")],
+            programVyperCore.programId
+        );
+
+        const [foreignMint] = await createMintAndVault(provider, 1);
+        const foreignReserve = await createTokenAccount(
+            provider,
+            foreignMint,
+            anchor.web3.Keypair.generate().publicKey
+        );
+        try {
+            await programVyperCore.methods
+                .initFeeSplit({
+                    recipients: [
+                        { tokenAccount: curatorReserve, weightBps: 7000 },
+                        { tokenAccount: foreignReserve, weightBps: 3000 },
+                    ],
+                })
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                    feeSplit,
+                })
+                .remainingAccounts([
+                    { pubkey: curatorReserve, isSigner: false, isWritable: false },
+                    { pubkey: foreignReserve, isSigner: false, isWritable: false },
+                ])
+                .rpc();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await programVyperCore.methods
+            .initFeeSplit({
+                recipients: [
+                    { tokenAccount: curatorReserve, weightBps: 7000 },
+                    { tokenAccount: treasuryReserve, weightBps: 3000 },
+                ],
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                feeSplit,
+            })
+            .remainingAccounts([
+                { pubkey: curatorReserve, isSigner: false, isWritable: false },
+                { pubkey: treasuryReserve, isSigner: false, isWritable: false },
+            ])
+            .rpc();
+
+        await rateMock.setFairValue(1000);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        await vyper.refreshTrancheFairValue();
+
+        await programVyperCore.methods
+            .collectFeeSplit()
+            .accounts({
+                trancheConfig: vyper.trancheConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                reserve: vyper.reserve,
+                feeSplit,
+            })
+            .remainingAccounts([
+                { pubkey: curatorReserve, isSigner: false, isWritable: true },
+                { pubkey: treasuryReserve, isSigner: false, isWritable: true },
+            ])
+            .rpc();
+
+        expect(await getTokenAccountAmount(provider, curatorReserve)).to.eql(21);
+        expect(await getTokenAccountAmount(provider, treasuryReserve)).to.eql(9);
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.eql(0);
+    });
+
+    it("protocol fee and kill switch", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5, 15); // with fee
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const treasury = anchor.web3.Keypair.generate().publicKey;
+        const treasuryReserve = await createTokenAccount(provider, reserveMint, treasury);
+        const updateProtocolConfig = async (bitmask: number, protocolFeeBps: number, haltFlags: number) =>
+            await programVyperCore.methods
+                .updateProtocolConfig({
+                    bitmask,
+                    admin: provider.wallet.publicKey,
+                    protocolFeeBps,
+                    treasury,
+                    haltFlags,
+                })
+                .accounts({
+                    admin: provider.wallet.publicKey,
+                    protocolConfig: vyper.protocolConfig,
+                })
+                .rpc();
+
+        await updateProtocolConfig(
+            UPDATE_PROTOCOL_CONFIG_FLAGS.PROTOCOL_FEE_BPS | UPDATE_PROTOCOL_CONFIG_FLAGS.TREASURY,
+            5000,
+            0
+        );
+
+        await rateMock.setFairValue(1000);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        await vyper.refreshTrancheFairValue();
+
+        let trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.eql(15);
+        expect(trancheConfigAccount.protocolFeeToCollectQuantity.toNumber()).to.eql(15);
+
+        await programVyperCore.methods
+            .collectProtocolFee()
+            .accounts({
+                protocolConfig: vyper.protocolConfig,
+                trancheConfig: vyper.trancheConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                reserve: vyper.reserve,
+                treasuryReserve,
+            })
+            .rpc();
+
+        expect(await getTokenAccountAmount(provider, treasuryReserve)).to.eql(15);
+        trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.protocolFeeToCollectQuantity.toNumber()).to.eql(0);
+
+        // halt refreshes for every tranche
+        await updateProtocolConfig(
+            UPDATE_PROTOCOL_CONFIG_FLAGS.PROTOCOL_FEE_BPS | UPDATE_PROTOCOL_CONFIG_FLAGS.HALT_FLAGS,
+            0,
+            TRANCHE_HALT_FLAGS.HALT_REFRESHES
+        );
+        try {
+            await vyper.refreshTrancheFairValue();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await updateProtocolConfig(UPDATE_PROTOCOL_CONFIG_FLAGS.HALT_FLAGS, 0, TRANCHE_HALT_FLAGS.NONE);
+        await vyper.refreshTrancheFairValue();
+    });
+
+    it("entry and exit fees", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.ENTRY_FEE_BPS | UPDATE_TRANCHE_CONFIG_FLAGS.EXIT_FEE_BPS,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [100, 200],
+                exitFeeBps: [50, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        let trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.depositedQuantity.map((c) => c.toNumber())).to.eql([
+            990 * 10 ** trancheMintDecimals,
+            490 * 10 ** trancheMintDecimals,
+        ]);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.eql(20 * 10 ** trancheMintDecimals);
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(990 * 10 ** trancheMintDecimals);
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(490 * 10 ** trancheMintDecimals);
+
+        const redeemTx = new anchor.web3.Transaction();
+        redeemTx.add(await rateMock.getRefreshIX());
+        redeemTx.add(await vyper.getRefreshTrancheFairValueIX());
+        redeemTx.add(
+            await vyper.getRedeemIx(
+                await getTokenAccountAmount(provider, seniorTrancheTokenAccount),
+                await getTokenAccountAmount(provider, juniorTrancheTokenAccount),
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(redeemTx);
+
+        trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.depositedQuantity.map((c) => c.toNumber())).to.eql([0, 0]);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.eql(24_950_000);
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(
+            seniorDepositAmount + juniorDepositAmount - 24_950_000
+        );
+    });
+
+    it("migrate tranche config", async () => {
+        const reserveMint = await createMint(provider);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals: 6, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .migrate()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                reserveMint,
+                seniorTrancheMint: vyper.seniorTrancheMint,
+            })
+            .rpc();
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.version).to.eql([0, 4, 0]);
+        expect(trancheConfigAccount.reserveMintDecimals).to.eql(
+            (await getMint(provider.connection, reserveMint)).decimals
+        );
+        expect(trancheConfigAccount.trancheMintDecimals).to.eql(6);
+        expect(trancheConfigAccount.owner.toBase58()).to.eql(provider.wallet.publicKey.toBase58());
+    });
+
+    it("emit deposit event", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        let depositEvent = undefined;
+        const listener = programVyperCore.addEventListener("Deposited", (event) => {
+            depositEvent = event;
+        });
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+        await programVyperCore.removeEventListener(listener);
+
+        expect(depositEvent.trancheConfig.toBase58()).to.eql(vyper.trancheConfig.toBase58());
+        expect(depositEvent.signer.toBase58()).to.eql(provider.wallet.publicKey.toBase58());
+        expect(depositEvent.mintedQuantity.map((c) => c.toNumber())).to.eql([seniorDepositAmount, juniorDepositAmount]);
+        expect(depositEvent.entryFeeQuantity.map((c) => c.toNumber())).to.eql([0, 0]);
+    });
+
+    it("expect error on deposit slippage", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount,
+                [seniorDepositAmount + 1, juniorDepositAmount]
+            )
+        );
+        try {
+            await provider.sendAndConfirm(depositTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(
+            seniorDepositAmount + juniorDepositAmount
+        );
+    });
+
+    it("refresh on demand inside deposit and redeem", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount,
+                [null, null],
+                true
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        const redeemTx = new anchor.web3.Transaction();
+        redeemTx.add(await rateMock.getRefreshIX());
+        redeemTx.add(
+            await vyper.getRedeemIx(
+                await getTokenAccountAmount(provider, seniorTrancheTokenAccount),
+                await getTokenAccountAmount(provider, juniorTrancheTokenAccount),
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount,
+                null,
+                true
+            )
+        );
+        await provider.sendAndConfirm(redeemTx);
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.depositedQuantity.map((c) => c.toNumber())).to.eql([0, 0]);
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(
+            seniorDepositAmount + juniorDepositAmount
+        );
+    });
+
+    it("deposit restricted to allowlisted users", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.ALLOWLIST_RESTRICTED_IXS,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: ALLOWLIST_RESTRICTED_IX_FLAGS.DEPOSITS,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const notAllowlistedTx = new anchor.web3.Transaction();
+        notAllowlistedTx.add(await rateMock.getRefreshIX());
+        notAllowlistedTx.add(await vyper.getRefreshTrancheFairValueIX());
+        notAllowlistedTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        try {
+            await provider.sendAndConfirm(notAllowlistedTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const [allowlistEntry] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                vyper.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("allowlist"),
+                provider.wallet.publicKey.toBuffer(),
+            ],
+            programVyperCore.programId
+        );
+        await programVyperCore.methods
+            .addAllowlistEntry()
+            .accounts({
+                authority: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                user: provider.wallet.publicKey,
+                allowlistEntry,
+            })
+            .rpc();
+
+        const depositIx = await vyper.getDepositIx(
+            seniorDepositAmount,
+            juniorDepositAmount,
+            userReserveToken,
+            seniorTrancheTokenAccount,
+            juniorTrancheTokenAccount
+        );
+        depositIx.keys.push({ pubkey: allowlistEntry, isSigner: false, isWritable: false });
+        const allowlistedTx = new anchor.web3.Transaction();
+        allowlistedTx.add(await rateMock.getRefreshIX());
+        allowlistedTx.add(await vyper.getRefreshTrancheFairValueIX());
+        allowlistedTx.add(depositIx);
+        await provider.sendAndConfirm(allowlistedTx);
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+
+        await programVyperCore.methods
+            .removeAllowlistEntry()
+            .accounts({
+                authority: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                allowlistEntry,
+            })
+            .rpc();
+        expect(await provider.connection.getAccountInfo(allowlistEntry)).to.be.null;
+    });
+
+    it("deposit capped per wallet", async () => {
+        const trancheMintDecimals = 6;
+        const walletDepositCap = 1000 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(provider, 4 * walletDepositCap);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.WALLET_DEPOSIT_CAP,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [bn(walletDepositCap), null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const [userPosition] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                vyper.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("user_position"),
+                provider.wallet.publicKey.toBuffer(),
+            ],
+            programVyperCore.programId
+        );
+        await programVyperCore.methods
+            .initUserPosition()
+            .accounts({
+                payer: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                user: provider.wallet.publicKey,
+                userPosition,
+            })
+            .rpc();
+
+        const getPositionDepositIx = async (seniorDepositAmount: number) => {
+            const depositIx = await vyper.getDepositIx(
+                seniorDepositAmount,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            );
+            depositIx.keys.push({ pubkey: userPosition, isSigner: false, isWritable: true });
+            return depositIx;
+        };
+
+        const overCapTx = new anchor.web3.Transaction();
+        overCapTx.add(await rateMock.getRefreshIX());
+        overCapTx.add(await vyper.getRefreshTrancheFairValueIX());
+        overCapTx.add(await getPositionDepositIx(walletDepositCap + 1));
+        try {
+            await provider.sendAndConfirm(overCapTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const withinCapTx = new anchor.web3.Transaction();
+        withinCapTx.add(await rateMock.getRefreshIX());
+        withinCapTx.add(await vyper.getRefreshTrancheFairValueIX());
+        withinCapTx.add(await getPositionDepositIx(walletDepositCap));
+        await provider.sendAndConfirm(withinCapTx);
+
+        const userPositionAccount = await programVyperCore.account.userPosition.fetch(userPosition);
+        expect(userPositionAccount.depositedQuantity[0].toNumber()).to.eq(walletDepositCap);
+
+        await programVyperCore.methods
+            .setUserDepositCap([bn(2 * walletDepositCap), null])
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                userPosition,
+            })
+            .rpc();
+
+        const overrideTx = new anchor.web3.Transaction();
+        overrideTx.add(await rateMock.getRefreshIX());
+        overrideTx.add(await vyper.getRefreshTrancheFairValueIX());
+        overrideTx.add(await getPositionDepositIx(walletDepositCap));
+        await provider.sendAndConfirm(overrideTx);
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(2 * walletDepositCap);
+    });
+
+    it("expect error on deposit over the total value cap", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        // total value cap fits the deposit at a reserve fair value of 1, not at 2
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.DEPOSIT_CAP_MODE | UPDATE_TRANCHE_CONFIG_FLAGS.TOTAL_DEPOSIT_CAP,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { reserveValue: {} },
+                totalDepositCap: bn(seniorDepositAmount + juniorDepositAmount),
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        await rateMock.setFairValue(2);
+        const overCapTx = new anchor.web3.Transaction();
+        overCapTx.add(await rateMock.getRefreshIX());
+        overCapTx.add(await vyper.getRefreshTrancheFairValueIX());
+        overCapTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        try {
+            await provider.sendAndConfirm(overCapTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await rateMock.setFairValue(1);
+        const withinCapTx = new anchor.web3.Transaction();
+        withinCapTx.add(await rateMock.getRefreshIX());
+        withinCapTx.add(await vyper.getRefreshTrancheFairValueIX());
+        withinCapTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(withinCapTx);
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+    });
+
+    it("expect error on deposit outside the coverage ratio bounds", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        // senior deposits between 1x and 3x the junior deposits
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: UPDATE_TRANCHE_CONFIG_FLAGS.COVERAGE_RATIO_BPS_BOUNDS,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [bn(10_000), bn(30_000)],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: 0,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const seniorOnlyTx = new anchor.web3.Transaction();
+        seniorOnlyTx.add(await rateMock.getRefreshIX());
+        seniorOnlyTx.add(await vyper.getRefreshTrancheFairValueIX());
+        seniorOnlyTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        try {
+            await provider.sendAndConfirm(seniorOnlyTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const withinBoundsTx = new anchor.web3.Transaction();
+        withinBoundsTx.add(await rateMock.getRefreshIX());
+        withinBoundsTx.add(await vyper.getRefreshTrancheFairValueIX());
+        withinBoundsTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(withinBoundsTx);
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(juniorDepositAmount);
+
+        // redeeming most of the junior side would leave the senior side uncovered
+        const partialRedeemTx = new anchor.web3.Transaction();
+        partialRedeemTx.add(await rateMock.getRefreshIX());
+        partialRedeemTx.add(await vyper.getRefreshTrancheFairValueIX());
+        partialRedeemTx.add(
+            await vyper.getRedeemIx(
+                0,
+                juniorDepositAmount - juniorDepositAmount / 10,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        try {
+            await provider.sendAndConfirm(partialRedeemTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        // the last junior holder can always exit
+        const exitTx = new anchor.web3.Transaction();
+        exitTx.add(await rateMock.getRefreshIX());
+        exitTx.add(await vyper.getRefreshTrancheFairValueIX());
+        exitTx.add(
+            await vyper.getRedeemIx(
+                0,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(exitTx);
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(0);
+
+        // the ratio is now above the maximum, senior redeems are accepted as they do not move it further away
+        const seniorRedeemTx = new anchor.web3.Transaction();
+        seniorRedeemTx.add(await rateMock.getRefreshIX());
+        seniorRedeemTx.add(await vyper.getRefreshTrancheFairValueIX());
+        seniorRedeemTx.add(
+            await vyper.getRedeemIx(
+                seniorDepositAmount / 2,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(seniorRedeemTx);
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount / 2);
+    });
+
+    it("deposit during subscription and redeem after maturity", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const updateLifecycle = async (lifecycle) => {
+            await programVyperCore.methods
+                .updateTrancheData({
+                    bitmask: 0,
+                    haltFlags: 0,
+                    ownerRestrictedIxs: 0,
+                    reserveFairValueStaleSlotThreshold: bn(2),
+                    trancheFairValueStaleSlotThreshold: bn(2),
+                    depositCap: [null, null],
+                    timelockSlots: bn(0),
+                    guardian: null,
+                    reserveSurplusDestination: { untracked: {} },
+                    entryFeeBps: [0, 0],
+                    exitFeeBps: [0, 0],
+                    allowlistRestrictedIxs: 0,
+                    allowlistAdmin: null,
+                    walletDepositCap: [null, null],
+                    depositCapMode: { quantity: {} },
+                    totalDepositCap: null,
+                    coverageRatioBpsBounds: [null, null],
+                    lifecycle,
+                    reserveFairValueStaleSecondsThreshold: bn(0),
+                    trancheFairValueStaleSecondsThreshold: bn(0),
+                    reserveFairValueStalenessMode: { slots: {} },
+                    trancheFairValueStalenessMode: { slots: {} },
+                    orphanedQuantityDestination: { tranche: {} },
+                    minInitialDeposit: [bn(0), bn(0)],
+                    deadSharesQuantity: bn(0),
+                    bitmaskExt: UPDATE_TRANCHE_CONFIG_EXT_FLAGS.LIFECYCLE,
+                })
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                })
+                .rpc();
+        };
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const getDepositTx = async () => {
+            const tx = new anchor.web3.Transaction();
+            tx.add(await rateMock.getRefreshIX());
+            tx.add(await vyper.getRefreshTrancheFairValueIX());
+            tx.add(
+                await vyper.getDepositIx(
+                    seniorDepositAmount,
+                    juniorDepositAmount,
+                    userReserveToken,
+                    seniorTrancheTokenAccount,
+                    juniorTrancheTokenAccount
+                )
+            );
+            return tx;
+        };
+
+        const now = Math.floor(Date.now() / 1000);
+
+        // subscription not started yet
+        await updateLifecycle({
+            subscriptionStart: bn(now + 3600),
+            subscriptionEnd: null,
+            maturity: null,
+            redeemBeforeMaturity: false,
+        });
+        try {
+            await provider.sendAndConfirm(await getDepositTx());
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        // subscription open, maturity not reached yet
+        await updateLifecycle({
+            subscriptionStart: null,
+            subscriptionEnd: bn(now + 3600),
+            maturity: bn(now + 7200),
+            redeemBeforeMaturity: false,
+        });
+        await provider.sendAndConfirm(await getDepositTx());
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+
+        const getRedeemTx = async () => {
+            const tx = new anchor.web3.Transaction();
+            tx.add(await rateMock.getRefreshIX());
+            tx.add(await vyper.getRefreshTrancheFairValueIX());
+            tx.add(
+                await vyper.getRedeemIx(
+                    seniorDepositAmount,
+                    juniorDepositAmount,
+                    userReserveToken,
+                    seniorTrancheTokenAccount,
+                    juniorTrancheTokenAccount
+                )
+            );
+            return tx;
+        };
+        try {
+            await provider.sendAndConfirm(await getRedeemTx());
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        // redeems allowed before maturity
+        await updateLifecycle({
+            subscriptionStart: null,
+            subscriptionEnd: bn(now + 3600),
+            maturity: bn(now + 7200),
+            redeemBeforeMaturity: true,
+        });
+        await provider.sendAndConfirm(await getRedeemTx());
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(0);
+    });
+
+    it("settle freezes fair values after maturity", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        // settlement rejected before maturity
+        const earlySettleTx = new anchor.web3.Transaction();
+        earlySettleTx.add(await rateMock.getRefreshIX());
+        earlySettleTx.add(await vyper.getSettleIX());
+        try {
+            await provider.sendAndConfirm(earlySettleTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: 0,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: {
+                    subscriptionStart: null,
+                    subscriptionEnd: null,
+                    maturity: bn(Math.floor(Date.now() / 1000) - 60),
+                    redeemBeforeMaturity: false,
+                },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: UPDATE_TRANCHE_CONFIG_EXT_FLAGS.LIFECYCLE,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const settleTx = new anchor.web3.Transaction();
+        settleTx.add(await rateMock.getRefreshIX());
+        settleTx.add(await vyper.getSettleIX());
+        await provider.sendAndConfirm(settleTx);
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.settled).to.be.true;
+
+        // refreshes rejected after settlement
+        await rateMock.setFairValue(2);
+        const refreshTx = new anchor.web3.Transaction();
+        refreshTx.add(await rateMock.getRefreshIX());
+        refreshTx.add(await vyper.getRefreshTrancheFairValueIX());
+        try {
+            await provider.sendAndConfirm(refreshTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        // redeems use the settled fair values without refresh
+        const redeemTx = new anchor.web3.Transaction();
+        redeemTx.add(
+            await vyper.getRedeemIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(redeemTx);
+
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+    });
+
+    it("roll epoch after settlement", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let nextRedeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await nextRedeemLogic.initialize(0.7);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        const now = Math.floor(Date.now() / 1000);
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: 0,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: {
+                    subscriptionStart: null,
+                    subscriptionEnd: null,
+                    maturity: bn(now - 60),
+                    redeemBeforeMaturity: false,
+                },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: UPDATE_TRANCHE_CONFIG_EXT_FLAGS.LIFECYCLE,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const [epochRecord] = await anchor.web3.PublicKey.findProgramAddress(
+            [vyper.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode("epoch"), bn(0).toArrayLike(Buffer, "le", 8)],
+            programVyperCore.programId
+        );
+        const [pendingEpochRoll] = await anchor.web3.PublicKey.findProgramAddress(
+            [vyper.trancheConfig.toBuffer(), anchor.utils.bytes.utf8.encode("pending_epoch_roll")],
+            programVyperCore.programId
+        );
+        await programVyperCore.methods
+            .queueEpochRoll({
+                nextLifecycle: {
+                    subscriptionStart: null,
+                    subscriptionEnd: bn(now + 3600),
+                    maturity: bn(now + 7200),
+                    redeemBeforeMaturity: false,
+                },
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                pendingEpochRoll,
+                redeemLogicProgram: nextRedeemLogic.programID,
+                nextRedeemLogicProgramState: nextRedeemLogic.state,
+            })
+            .rpc();
+
+        const rollEpoch = async () => {
+            await programVyperCore.methods
+                .rollEpoch()
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                    epochRecord,
+                    pendingEpochRoll,
+                    redeemLogicProgram: nextRedeemLogic.programID,
+                    nextRedeemLogicProgramState: nextRedeemLogic.state,
+                })
+                .rpc();
+        };
+
+        // roll rejected before settlement
+        try {
+            await rollEpoch();
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const settleTx = new anchor.web3.Transaction();
+        settleTx.add(await rateMock.getRefreshIX());
+        settleTx.add(await vyper.getSettleIX());
+        await provider.sendAndConfirm(settleTx);
+
+        await rollEpoch();
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.epoch.toNumber()).to.eq(1);
+        expect(trancheConfigAccount.settled).to.be.false;
+        expect(trancheConfigAccount.redeemLogicProgramState.toBase58()).to.eq(nextRedeemLogic.state.toBase58());
+        expect(trancheConfigAccount.trancheData.depositedQuantity[0].toNumber()).to.eq(seniorDepositAmount);
+
+        expect(await provider.connection.getAccountInfo(pendingEpochRoll)).to.be.null;
+
+        const epochRecordAccount = await programVyperCore.account.epochRecord.fetch(epochRecord);
+        expect(epochRecordAccount.epoch.toNumber()).to.eq(0);
+        expect(epochRecordAccount.redeemLogicProgramState.toBase58()).to.eq(redeemLogic.state.toBase58());
+
+        // positions rolled into the next epoch
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(juniorDepositAmount);
+    });
+
+    it("queued deposit processed after refresh and claimed", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const [queuedRequest] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                vyper.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("queued_request"),
+                provider.wallet.publicKey.toBuffer(),
+            ],
+            programVyperCore.programId
+        );
+        const [seniorTrancheEscrow] = await anchor.web3.PublicKey.findProgramAddress(
+            [queuedRequest.toBuffer(), anchor.utils.bytes.utf8.encode("senior_escrow")],
+            programVyperCore.programId
+        );
+        const [juniorTrancheEscrow] = await anchor.web3.PublicKey.findProgramAddress(
+            [queuedRequest.toBuffer(), anchor.utils.bytes.utf8.encode("junior_escrow")],
+            programVyperCore.programId
+        );
+
+        await programVyperCore.methods
+            .createQueuedRequest({
+                kind: { deposit: {} },
+                quantity: [bn(seniorDepositAmount), bn(juniorDepositAmount)],
+                minTrancheOut: [null, null],
+                minReserveOut: null,
+            })
+            .accounts({
+                signer: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                protocolConfig: vyper.protocolConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                queuedRequest,
+                reserve: vyper.reserve,
+                userReserveToken,
+                seniorTrancheMint: vyper.seniorTrancheMint,
+                juniorTrancheMint: vyper.juniorTrancheMint,
+                seniorTrancheSource: seniorTrancheTokenAccount,
+                juniorTrancheSource: juniorTrancheTokenAccount,
+                seniorTrancheEscrow,
+                juniorTrancheEscrow,
+            })
+            .rpc();
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(0);
+
+        const getProcessIx = async () =>
+            await programVyperCore.methods
+                .processQueuedRequest()
+                .accounts({
+                    signer: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                    protocolConfig: vyper.protocolConfig,
+                    trancheAuthority: vyper.trancheAuthority,
+                    queuedRequest,
+                    seniorTrancheMint: vyper.seniorTrancheMint,
+                    juniorTrancheMint: vyper.juniorTrancheMint,
+                    seniorTrancheEscrow,
+                    juniorTrancheEscrow,
+                })
+                .instruction();
+
+        // processing requires a fair value refreshed after the request
+        const notRefreshedTx = new anchor.web3.Transaction();
+        notRefreshedTx.add(await getProcessIx());
+        try {
+            await provider.sendAndConfirm(notRefreshedTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+        const processTx = new anchor.web3.Transaction();
+        processTx.add(await rateMock.getRefreshIX());
+        processTx.add(await vyper.getRefreshTrancheFairValueIX());
+        processTx.add(await getProcessIx());
+        await provider.sendAndConfirm(processTx);
+
+        const queuedRequestAccount = await programVyperCore.account.queuedRequest.fetch(queuedRequest);
+        expect(queuedRequestAccount.processed).to.be.true;
+        expect(await getTokenAccountAmount(provider, seniorTrancheEscrow)).to.eq(seniorDepositAmount);
+
+        await programVyperCore.methods
+            .claimQueuedRequest()
+            .accounts({
+                signer: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                queuedRequest,
+                reserve: vyper.reserve,
+                userReserveToken,
+                seniorTrancheDest: seniorTrancheTokenAccount,
+                juniorTrancheDest: juniorTrancheTokenAccount,
+                seniorTrancheEscrow,
+                juniorTrancheEscrow,
+            })
+            .rpc();
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(juniorDepositAmount);
+        expect(await provider.connection.getAccountInfo(queuedRequest)).to.be.null;
+    });
+
+    it("stale fair values judged by seconds", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            2 * (seniorDepositAmount + juniorDepositAmount)
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const updateStalenessMode = async (stalenessMode) => {
+            await programVyperCore.methods
+                .updateTrancheData({
+                    bitmask: 0,
+                    haltFlags: 0,
+                    ownerRestrictedIxs: 0,
+                    reserveFairValueStaleSlotThreshold: bn(2),
+                    trancheFairValueStaleSlotThreshold: bn(2),
+                    depositCap: [null, null],
+                    timelockSlots: bn(0),
+                    guardian: null,
+                    reserveSurplusDestination: { untracked: {} },
+                    entryFeeBps: [0, 0],
+                    exitFeeBps: [0, 0],
+                    allowlistRestrictedIxs: 0,
+                    allowlistAdmin: null,
+                    walletDepositCap: [null, null],
+                    depositCapMode: { quantity: {} },
+                    totalDepositCap: null,
+                    coverageRatioBpsBounds: [null, null],
+                    lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                    reserveFairValueStaleSecondsThreshold: bn(3600),
+                    trancheFairValueStaleSecondsThreshold: bn(3600),
+                    reserveFairValueStalenessMode: stalenessMode,
+                    trancheFairValueStalenessMode: stalenessMode,
+                    orphanedQuantityDestination: { tranche: {} },
+                    minInitialDeposit: [bn(0), bn(0)],
+                    deadSharesQuantity: bn(0),
+                    bitmaskExt:
+                        UPDATE_TRANCHE_CONFIG_EXT_FLAGS.RESERVE_FAIR_VALUE_STALE_SECONDS_THRESHOLD |
+                        UPDATE_TRANCHE_CONFIG_EXT_FLAGS.TRANCHE_FAIR_VALUE_STALE_SECONDS_THRESHOLD |
+                        UPDATE_TRANCHE_CONFIG_EXT_FLAGS.RESERVE_FAIR_VALUE_STALENESS_MODE |
+                        UPDATE_TRANCHE_CONFIG_EXT_FLAGS.TRANCHE_FAIR_VALUE_STALENESS_MODE,
+                })
+                .accounts({
+                    owner: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                })
+                .rpc();
+        };
+        await updateStalenessMode({ seconds: {} });
+
+        const refreshTx = new anchor.web3.Transaction();
+        refreshTx.add(await rateMock.getRefreshIX());
+        refreshTx.add(await vyper.getRefreshTrancheFairValueIX());
+        await provider.sendAndConfirm(refreshTx);
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        //@ts-expect-error
+        expect(trancheConfigAccount.trancheData.trancheFairValue.slotTracking.lastUpdate.unixTimestamp.toNumber()).to.be.gt(0);
+
+        // the fair values are older than the stale slot threshold but fresher than the stale seconds threshold
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+        const getDepositTx = async () =>
+            new anchor.web3.Transaction().add(
+                await vyper.getDepositIx(
+                    seniorDepositAmount,
+                    juniorDepositAmount,
+                    userReserveToken,
+                    seniorTrancheTokenAccount,
+                    juniorTrancheTokenAccount
+                )
+            );
+        await provider.sendAndConfirm(await getDepositTx());
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+
+        // stale once either threshold is reached
+        await updateStalenessMode({ slotsOrSeconds: {} });
+        try {
+            await provider.sendAndConfirm(await getDepositTx());
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+    });
+
+    it("orphaned quantity routed to fees", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(provider, seniorDepositAmount);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: 0,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { fee: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: UPDATE_TRANCHE_CONFIG_EXT_FLAGS.ORPHANED_QUANTITY_DESTINATION,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        // senior side only, the junior tranche has no holders
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        // the lending plugin assigns part of the interest to the junior side
+        await rateMock.setFairValue(2);
+        const refreshTx = new anchor.web3.Transaction();
+        refreshTx.add(await rateMock.getRefreshIX());
+        refreshTx.add(await vyper.getRefreshTrancheFairValueIX());
+        await provider.sendAndConfirm(refreshTx);
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.depositedQuantity[1].toNumber()).to.eq(0);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.be.gt(0);
+        expect(
+            trancheConfigAccount.trancheData.depositedQuantity[0].toNumber() +
+                trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber() +
+                trancheConfigAccount.protocolFeeToCollectQuantity.toNumber()
+        ).to.eq(seniorDepositAmount);
+    });
+
+    it("orphaned quantity removed with a fair value reset", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(provider, seniorDepositAmount);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: 0,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { resetFairValue: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(0),
+                bitmaskExt: UPDATE_TRANCHE_CONFIG_EXT_FLAGS.ORPHANED_QUANTITY_DESTINATION,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        // senior side only, the junior tranche has no holders
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        // the lending plugin assigns part of the interest to the junior side
+        await rateMock.setFairValue(2);
+        const refreshTx = new anchor.web3.Transaction();
+        refreshTx.add(await rateMock.getRefreshIX());
+        refreshTx.add(await vyper.getRefreshTrancheFairValueIX());
+        await provider.sendAndConfirm(refreshTx);
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.trancheData.depositedQuantity[1].toNumber()).to.eq(0);
+        expect(trancheConfigAccount.trancheData.feeToCollectQuantity.toNumber()).to.eq(0);
+        expect(
+            new RustDecimalWrapper(new Uint8Array(trancheConfigAccount.trancheData.trancheFairValue.value[1])).toNumber()
+        ).to.eq(1);
+    });
+
+    it("first deposit locks dead shares", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const minInitialDeposit = 100 * 10 ** trancheMintDecimals;
+        const deadSharesQuantity = 1000;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+        await vyper.initDeadShares();
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: 0,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(minInitialDeposit), bn(minInitialDeposit)],
+                deadSharesQuantity: bn(deadSharesQuantity),
+                bitmaskExt:
+                    UPDATE_TRANCHE_CONFIG_EXT_FLAGS.MIN_INITIAL_DEPOSIT |
+                    UPDATE_TRANCHE_CONFIG_EXT_FLAGS.DEAD_SHARES_QUANTITY,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const [seniorTrancheDeadShares, juniorTrancheDeadShares] = await vyper.getDeadSharesAccounts();
+
+        // first deposit below the minimum
+        try {
+            const depositTx = new anchor.web3.Transaction();
+            depositTx.add(await rateMock.getRefreshIX());
+            depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+            const depositIx = await vyper.getDepositIx(
+                minInitialDeposit - 1,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            );
+            depositIx.keys.push({ pubkey: seniorTrancheDeadShares, isSigner: false, isWritable: true });
+            depositIx.keys.push({ pubkey: juniorTrancheDeadShares, isSigner: false, isWritable: true });
+            depositTx.add(depositIx);
+            await provider.sendAndConfirm(depositTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        const depositIx = await vyper.getDepositIx(
+            seniorDepositAmount,
+            juniorDepositAmount,
+            userReserveToken,
+            seniorTrancheTokenAccount,
+            juniorTrancheTokenAccount
+        );
+        depositIx.keys.push({ pubkey: seniorTrancheDeadShares, isSigner: false, isWritable: true });
+        depositIx.keys.push({ pubkey: juniorTrancheDeadShares, isSigner: false, isWritable: true });
+        depositTx.add(depositIx);
+        await provider.sendAndConfirm(depositTx);
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheDeadShares)).to.eq(deadSharesQuantity);
+        expect(await getTokenAccountAmount(provider, juniorTrancheDeadShares)).to.eq(deadSharesQuantity);
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(
+            seniorDepositAmount - deadSharesQuantity
+        );
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(
+            juniorDepositAmount - deadSharesQuantity
+        );
+    });
+
+    it("tranche mint decimals differ from the reserve mint decimals", async () => {
+        const reserveMintDecimals = 9;
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** reserveMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** reserveMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount,
+            reserveMintDecimals
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        let trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.reserveMintDecimals).to.eq(reserveMintDecimals);
+        expect(trancheConfigAccount.trancheMintDecimals).to.eq(trancheMintDecimals);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        // one whole reserve token mints one whole tranche token
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(
+            1000 * 10 ** trancheMintDecimals
+        );
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(500 * 10 ** trancheMintDecimals);
+
+        const refreshTx = new anchor.web3.Transaction();
+        refreshTx.add(await rateMock.getRefreshIX());
+        refreshTx.add(await vyper.getRefreshTrancheFairValueIX());
+        await provider.sendAndConfirm(refreshTx);
+
+        trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(
+            //@ts-expect-error
+            trancheConfigAccount.trancheData.trancheFairValue.value.map((c) =>
+                new RustDecimalWrapper(new Uint8Array(c)).toNumber()
+            )
+        ).to.eql(Array(2).fill(1));
+
+        const redeemTx = new anchor.web3.Transaction();
+        redeemTx.add(await rateMock.getRefreshIX());
+        redeemTx.add(await vyper.getRefreshTrancheFairValueIX());
+        redeemTx.add(
+            await vyper.getRedeemIx(
+                await getTokenAccountAmount(provider, seniorTrancheTokenAccount),
+                await getTokenAccountAmount(provider, juniorTrancheTokenAccount),
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(redeemTx);
+
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(
+            seniorDepositAmount + juniorDepositAmount
+        );
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(0);
+        expect(await getTokenAccountAmount(provider, juniorTrancheTokenAccount)).to.eq(0);
+    });
+
+    it("redeem rejects a foreign tranche mint", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        depositTx.add(
+            await vyper.getDepositIx(
+                seniorDepositAmount,
+                juniorDepositAmount,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(depositTx);
+
+        // a mint with an arbitrary supply in place of the senior tranche mint
+        const foreignMint = await createMint(provider, trancheMintDecimals);
+        const redeemIx = await vyper.getRedeemIx(
+            seniorDepositAmount,
+            0,
+            userReserveToken,
+            seniorTrancheTokenAccount,
+            juniorTrancheTokenAccount,
+            null,
+            true
+        );
+        redeemIx.keys
+            .filter((key) => key.pubkey.equals(vyper.seniorTrancheMint))
+            .forEach((key) => (key.pubkey = foreignMint));
+
+        try {
+            await provider.sendAndConfirm(new anchor.web3.Transaction().add(redeemIx));
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(seniorDepositAmount);
+    });
+
+    it("queued deposit below the minimum out rejected after refresh", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const [queuedRequest] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                vyper.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("queued_request"),
+                provider.wallet.publicKey.toBuffer(),
+            ],
+            programVyperCore.programId
+        );
+        const [seniorTrancheEscrow] = await anchor.web3.PublicKey.findProgramAddress(
+            [queuedRequest.toBuffer(), anchor.utils.bytes.utf8.encode("senior_escrow")],
+            programVyperCore.programId
+        );
+        const [juniorTrancheEscrow] = await anchor.web3.PublicKey.findProgramAddress(
+            [queuedRequest.toBuffer(), anchor.utils.bytes.utf8.encode("junior_escrow")],
+            programVyperCore.programId
+        );
+
+        // more senior tranches than the deposit can mint at a fair value of one
+        await programVyperCore.methods
+            .createQueuedRequest({
+                kind: { deposit: {} },
+                quantity: [bn(seniorDepositAmount), bn(juniorDepositAmount)],
+                minTrancheOut: [bn(seniorDepositAmount + 1), null],
+                minReserveOut: null,
+            })
+            .accounts({
+                signer: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                protocolConfig: vyper.protocolConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                queuedRequest,
+                reserve: vyper.reserve,
+                userReserveToken,
+                seniorTrancheMint: vyper.seniorTrancheMint,
+                juniorTrancheMint: vyper.juniorTrancheMint,
+                seniorTrancheSource: seniorTrancheTokenAccount,
+                juniorTrancheSource: juniorTrancheTokenAccount,
+                seniorTrancheEscrow,
+                juniorTrancheEscrow,
+            })
+            .rpc();
+
+        const getClaimIx = async () =>
+            await programVyperCore.methods
+                .claimQueuedRequest()
+                .accounts({
+                    signer: provider.wallet.publicKey,
+                    trancheConfig: vyper.trancheConfig,
+                    trancheAuthority: vyper.trancheAuthority,
+                    queuedRequest,
+                    reserve: vyper.reserve,
+                    userReserveToken,
+                    seniorTrancheDest: seniorTrancheTokenAccount,
+                    juniorTrancheDest: juniorTrancheTokenAccount,
+                    seniorTrancheEscrow,
+                    juniorTrancheEscrow,
+                })
+                .instruction();
+
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+        const refreshTx = new anchor.web3.Transaction();
+        refreshTx.add(await rateMock.getRefreshIX());
+        refreshTx.add(await vyper.getRefreshTrancheFairValueIX());
+        await provider.sendAndConfirm(refreshTx);
+
+        // the execution fair value is known, the request can't be cancelled anymore
+        try {
+            await provider.sendAndConfirm(new anchor.web3.Transaction().add(await getClaimIx()));
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        await programVyperCore.methods
+            .processQueuedRequest()
+            .accounts({
+                signer: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                protocolConfig: vyper.protocolConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                queuedRequest,
+                seniorTrancheMint: vyper.seniorTrancheMint,
+                juniorTrancheMint: vyper.juniorTrancheMint,
+                seniorTrancheEscrow,
+                juniorTrancheEscrow,
+            })
+            .rpc();
+
+        const queuedRequestAccount = await programVyperCore.account.queuedRequest.fetch(queuedRequest);
+        expect(queuedRequestAccount.processed).to.be.true;
+        expect(queuedRequestAccount.reserveToClaimQuantity.toNumber()).to.eq(seniorDepositAmount + juniorDepositAmount);
+        expect(await getTokenAccountAmount(provider, seniorTrancheEscrow)).to.eq(0);
+
+        await provider.sendAndConfirm(new anchor.web3.Transaction().add(await getClaimIx()));
+
+        expect(await getTokenAccountAmount(provider, userReserveToken)).to.eq(
+            seniorDepositAmount + juniorDepositAmount
+        );
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(0);
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.queuedReserveQuantity.toNumber()).to.eq(0);
+        expect(trancheConfigAccount.trancheData.depositedQuantity.map((c) => c.toNumber())).to.eql([0, 0]);
+    });
+
+    it("close tranche after the first deposit locked dead shares", async () => {
+        const trancheMintDecimals = 6;
+        const seniorDepositAmount = 1000 * 10 ** trancheMintDecimals;
+        const juniorDepositAmount = 500 * 10 ** trancheMintDecimals;
+        const deadSharesQuantity = 1000;
+        const [reserveMint, userReserveToken] = await createMintAndVault(
+            provider,
+            seniorDepositAmount + juniorDepositAmount
+        );
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+        await vyper.initDeadShares();
+
+        await programVyperCore.methods
+            .updateTrancheData({
+                bitmask: 0,
+                haltFlags: 0,
+                ownerRestrictedIxs: 0,
+                reserveFairValueStaleSlotThreshold: bn(2),
+                trancheFairValueStaleSlotThreshold: bn(2),
+                depositCap: [null, null],
+                timelockSlots: bn(0),
+                guardian: null,
+                reserveSurplusDestination: { untracked: {} },
+                entryFeeBps: [0, 0],
+                exitFeeBps: [0, 0],
+                allowlistRestrictedIxs: 0,
+                allowlistAdmin: null,
+                walletDepositCap: [null, null],
+                depositCapMode: { quantity: {} },
+                totalDepositCap: null,
+                coverageRatioBpsBounds: [null, null],
+                lifecycle: { subscriptionStart: null, subscriptionEnd: null, maturity: null, redeemBeforeMaturity: false },
+                reserveFairValueStaleSecondsThreshold: bn(0),
+                trancheFairValueStaleSecondsThreshold: bn(0),
+                reserveFairValueStalenessMode: { slots: {} },
+                trancheFairValueStalenessMode: { slots: {} },
+                orphanedQuantityDestination: { tranche: {} },
+                minInitialDeposit: [bn(0), bn(0)],
+                deadSharesQuantity: bn(deadSharesQuantity),
+                bitmaskExt: UPDATE_TRANCHE_CONFIG_EXT_FLAGS.DEAD_SHARES_QUANTITY,
+            })
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+            })
+            .rpc();
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const [seniorTrancheDeadShares, juniorTrancheDeadShares] = await vyper.getDeadSharesAccounts();
+
+        const depositTx = new anchor.web3.Transaction();
+        depositTx.add(await rateMock.getRefreshIX());
+        depositTx.add(await vyper.getRefreshTrancheFairValueIX());
+        const depositIx = await vyper.getDepositIx(
+            seniorDepositAmount,
+            juniorDepositAmount,
+            userReserveToken,
+            seniorTrancheTokenAccount,
+            juniorTrancheTokenAccount
+        );
+        depositIx.keys.push({ pubkey: seniorTrancheDeadShares, isSigner: false, isWritable: true });
+        depositIx.keys.push({ pubkey: juniorTrancheDeadShares, isSigner: false, isWritable: true });
+        depositTx.add(depositIx);
+        await provider.sendAndConfirm(depositTx);
+
+        const redeemTx = new anchor.web3.Transaction();
+        redeemTx.add(await rateMock.getRefreshIX());
+        redeemTx.add(await vyper.getRefreshTrancheFairValueIX());
+        redeemTx.add(
+            await vyper.getRedeemIx(
+                await getTokenAccountAmount(provider, seniorTrancheTokenAccount),
+                await getTokenAccountAmount(provider, juniorTrancheTokenAccount),
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        await provider.sendAndConfirm(redeemTx);
+
+        // only the dead shares are left
+        expect((await getMint(provider.connection, vyper.seniorTrancheMint)).supply).to.eq(BigInt(deadSharesQuantity));
+
+        const destReserve = await createTokenAccount(provider, reserveMint, provider.wallet.publicKey);
+        await programVyperCore.methods
+            .closeTranche()
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                trancheAuthority: vyper.trancheAuthority,
+                reserve: vyper.reserve,
+                destReserve,
+                seniorTrancheMint: vyper.seniorTrancheMint,
+                juniorTrancheMint: vyper.juniorTrancheMint,
+                destination: provider.wallet.publicKey,
+            })
+            .remainingAccounts([
+                { pubkey: seniorTrancheDeadShares, isSigner: false, isWritable: true },
+                { pubkey: juniorTrancheDeadShares, isSigner: false, isWritable: true },
+                ...(await vyper.getCloseRemainingAccounts()),
+            ])
+            .rpc();
+
+        expect(await provider.connection.getAccountInfo(vyper.trancheConfig)).to.be.null;
+        expect(await provider.connection.getAccountInfo(seniorTrancheDeadShares)).to.be.null;
+        expect(await provider.connection.getAccountInfo(juniorTrancheDeadShares)).to.be.null;
+        expect((await getMint(provider.connection, vyper.seniorTrancheMint)).supply).to.eq(BigInt(0));
+        expect((await getMint(provider.connection, vyper.juniorTrancheMint)).supply).to.eq(BigInt(0));
+        expect(await getTokenAccountAmount(provider, destReserve)).to.eq(2 * deadSharesQuantity);
+    });
+
+    it("deposit capped by the user position override only", async () => {
+        const trancheMintDecimals = 6;
+        const walletDepositCap = 1000 * 10 ** trancheMintDecimals;
+        const [reserveMint, userReserveToken] = await createMintAndVault(provider, 4 * walletDepositCap);
+
+        let redeemLogic = RedeemLogicLendingPlugin.create(programRedeemLogicLending, provider);
+        let rateMock = RateMockPlugin.create(programRateMock, provider);
+        let vyper = Vyper.create(programVyperCore, provider);
+
+        await rateMock.initialize();
+        await redeemLogic.initialize(0.5);
+        await vyper.initialize(
+            { trancheMintDecimals, ownerRestrictedIxs: 0, haltFlags: 0 },
+            reserveMint,
+            rateMock.programID,
+            rateMock.state,
+            redeemLogic.programID,
+            redeemLogic.state
+        );
+        await rateMock.setFairValue(1);
+
+        const seniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.seniorTrancheMint,
+            provider.wallet.publicKey
+        );
+        const juniorTrancheTokenAccount = await createTokenAccount(
+            provider,
+            vyper.juniorTrancheMint,
+            provider.wallet.publicKey
+        );
+
+        const [userPosition] = await anchor.web3.PublicKey.findProgramAddress(
+            [
+                vyper.trancheConfig.toBuffer(),
+                anchor.utils.bytes.utf8.encode("user_position"),
+                provider.wallet.publicKey.toBuffer(),
+            ],
+            programVyperCore.programId
+        );
+        await programVyperCore.methods
+            .initUserPosition()
+            .accounts({
+                payer: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                user: provider.wallet.publicKey,
+                userPosition,
+            })
+            .rpc();
+
+        // no default wallet cap, only the override
+        await programVyperCore.methods
+            .setUserDepositCap([bn(walletDepositCap), null])
+            .accounts({
+                owner: provider.wallet.publicKey,
+                trancheConfig: vyper.trancheConfig,
+                userPosition,
+            })
+            .rpc();
+
+        const getPositionDepositIx = async (seniorDepositAmount: number) => {
+            const depositIx = await vyper.getDepositIx(
+                seniorDepositAmount,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            );
+            depositIx.keys.push({ pubkey: userPosition, isSigner: false, isWritable: true });
+            return depositIx;
+        };
+
+        const trancheConfigAccount = await programVyperCore.account.trancheConfig.fetch(vyper.trancheConfig);
+        expect(trancheConfigAccount.hasUserDepositCapOverride).to.be.true;
+
+        // the override can't be bypassed by omitting the user position
+        const noPositionTx = new anchor.web3.Transaction();
+        noPositionTx.add(await rateMock.getRefreshIX());
+        noPositionTx.add(await vyper.getRefreshTrancheFairValueIX());
+        noPositionTx.add(
+            await vyper.getDepositIx(
+                walletDepositCap + 1,
+                0,
+                userReserveToken,
+                seniorTrancheTokenAccount,
+                juniorTrancheTokenAccount
+            )
+        );
+        try {
+            await provider.sendAndConfirm(noPositionTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const overCapTx = new anchor.web3.Transaction();
+        overCapTx.add(await rateMock.getRefreshIX());
+        overCapTx.add(await vyper.getRefreshTrancheFairValueIX());
+        overCapTx.add(await getPositionDepositIx(walletDepositCap + 1));
+        try {
+            await provider.sendAndConfirm(overCapTx);
+            expect(false).to.be.true;
+        } catch (err) {
+            assert(true);
+        }
+
+        const withinCapTx = new anchor.web3.Transaction();
+        withinCapTx.add(await rateMock.getRefreshIX());
+        withinCapTx.add(await vyper.getRefreshTrancheFairValueIX());
+        withinCapTx.add(await getPositionDepositIx(walletDepositCap));
+        await provider.sendAndConfirm(withinCapTx);
+
+        const userPositionAccount = await programVyperCore.account.userPosition.fetch(userPosition);
+        expect(userPositionAccount.depositedQuantity[0].toNumber()).to.eq(walletDepositCap);
+        expect(await getTokenAccountAmount(provider, seniorTrancheTokenAccount)).to.eq(walletDepositCap);
+    });
 });